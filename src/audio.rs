@@ -0,0 +1,110 @@
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    audio::{output::Output, AudioSink, Source, SourceHandle, WavFormat},
+    prelude::*,
+};
+
+use serde::{Deserialize, Serialize};
+
+/// Read from `config/audio.ron`, volumes go from 0 to 1.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// Without audio, no sound device is needed.
+    pub enabled: bool,
+    pub music_volume: f32,
+    pub effects_volume: f32,
+}
+
+impl Default for AudioSettings {
+    fn default() -> Self {
+        AudioSettings {
+            enabled: true,
+            music_volume: 0.5,
+            effects_volume: 0.8,
+        }
+    }
+}
+
+pub struct Sounds {
+    pub bomb_placed: SourceHandle,
+    pub fuse: SourceHandle,
+    pub explosion: SourceHandle,
+    pub wall_destroyed: SourceHandle,
+    pub power_up: SourceHandle,
+    pub player_died: SourceHandle,
+}
+
+/// The track of the current map, handed to the `DjSystem` over and over. The
+/// hurry up variant takes over from the next loop once `hurry_up` is set.
+#[derive(Default)]
+pub struct Music {
+    tracks: Option<(SourceHandle, SourceHandle)>,
+    pub hurry_up: bool,
+}
+
+impl Music {
+    pub fn next(&mut self) -> Option<SourceHandle> {
+        let (normal, hurry) = self.tracks.as_ref()?;
+        if self.hurry_up {
+            Some(hurry.clone())
+        } else {
+            Some(normal.clone())
+        }
+    }
+
+    /// Stops looping once the current track is over.
+    pub fn stop(&mut self) {
+        self.tracks = None;
+    }
+}
+
+fn load_sound(world: &World, path: &str) -> SourceHandle {
+    let loader = world.read_resource::<Loader>();
+    let storage = world.read_resource::<AssetStorage<Source>>();
+    loader.load(path, WavFormat, (), &storage)
+}
+
+pub fn load_sounds(world: &World) -> Sounds {
+    Sounds {
+        bomb_placed: load_sound(world, "audio/bomb_placed.wav"),
+        fuse: load_sound(world, "audio/fuse.wav"),
+        explosion: load_sound(world, "audio/explosion.wav"),
+        wall_destroyed: load_sound(world, "audio/wall_destroyed.wav"),
+        power_up: load_sound(world, "audio/power_up.wav"),
+        player_died: load_sound(world, "audio/player_died.wav"),
+    }
+}
+
+/// Loops `name` and its `_hurry` variant from `audio/music`, the default
+/// track when `name` is empty.
+pub fn play_music(world: &World, name: &str) {
+    let name = if name.is_empty() { "default" } else { name };
+    let normal = load_sound(world, &format!("audio/music/{}.wav", name));
+    let hurry = load_sound(world, &format!("audio/music/{}_hurry.wav", name));
+    let mut music = world.write_resource::<Music>();
+    music.tracks = Some((normal, hurry));
+    music.hurry_up = false;
+}
+
+/// Applies the music volume of the `AudioSettings`, when there is a sound
+/// device to play on.
+pub fn apply_music_volume(world: &World) {
+    let volume = world.read_resource::<AudioSettings>().music_volume;
+    if let Some(mut sink) = world.try_fetch_mut::<AudioSink>() {
+        sink.set_volume(volume);
+    }
+}
+
+pub fn play_sound(
+    sound: &SourceHandle,
+    storage: &AssetStorage<Source>,
+    output: Option<&Output>,
+    volume: f32,
+) {
+    if let Some(output) = output {
+        if let Some(sound) = storage.get(sound) {
+            output.play_once(sound, volume);
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use std::fmt;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::entities::player::MAX_HUMAN_PLAYERS;
+use crate::net::{NetMode, DEFAULT_PORT};
+use crate::rules;
+use crate::sim::Difficulty;
+use crate::state::MIN_PLAYERS;
+
+pub const USAGE: &str = "\
+Usage: bomberman [OPTIONS]
+
+Options:
+    --map FILE|random       play on the map FILE, or on random maps
+    --humans N              number of human players, 1 by default
+    --bots N                number of bots, 3 by default
+    --difficulty LEVEL      how well the bots play: easy, normal or hard
+    --seed N                draw the maps and rounds from the seed N
+    --window WIDTHxHEIGHT   size of the window, e.g. 1024x768
+    --headless              play the match between bots without a window and
+                            print the result, humans included
+    --host [PORT|ADDRESS]   host an online game, on port 7777 by default
+    --connect ADDRESS       join the game hosted at ADDRESS, e.g. 127.0.0.1:7777
+    --record FILE           record the match into the replay FILE
+    --replay FILE           watch the match recorded in FILE
+    --rules PRESET          play with config/rules/PRESET.ron, classic by default
+    --no-audio              play without sound, no sound device needed
+    -h, --help              print this message";
+
+pub struct Options {
+    /// A map file, or `RANDOM_MAP` for a new random map every round.
+    pub map: Option<String>,
+    pub humans: Option<u8>,
+    pub bots: Option<u8>,
+    pub difficulty: Difficulty,
+    pub seed: Option<u64>,
+    pub window: Option<(u32, u32)>,
+    pub headless: bool,
+    pub net: NetMode,
+    pub record: Option<PathBuf>,
+    pub replay: Option<PathBuf>,
+    /// Name of a preset of `rules::RULES_DIR`.
+    pub rules: String,
+    pub no_audio: bool,
+    pub help: bool,
+}
+
+/// Stands for a new random map every round in place of a map file.
+pub const RANDOM_MAP: &str = "random";
+
+/// Parses the command line arguments, program name excluded.
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Options, String> {
+    let mut options = Options {
+        map: None,
+        humans: None,
+        bots: None,
+        difficulty: Difficulty::default(),
+        seed: None,
+        window: None,
+        headless: false,
+        net: NetMode::Local,
+        record: None,
+        replay: None,
+        rules: rules::DEFAULT_PRESET.to_string(),
+        no_audio: false,
+        help: false,
+    };
+    let mut args = args.into_iter().peekable();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => options.help = true,
+            "--no-audio" => options.no_audio = true,
+            "--headless" => options.headless = true,
+            "--map" => options.map = Some(value(&arg, args.next())?),
+            "--humans" => options.humans = Some(value(&arg, args.next())?),
+            "--bots" => options.bots = Some(value(&arg, args.next())?),
+            "--difficulty" => options.difficulty = value(&arg, args.next())?,
+            "--seed" => options.seed = Some(value(&arg, args.next())?),
+            "--window" => {
+                let size: String = value(&arg, args.next())?;
+                let mut parts = size.splitn(2, 'x').map(|part| part.parse::<u32>().ok());
+                let dimensions = match (parts.next(), parts.next()) {
+                    (Some(Some(width)), Some(Some(height))) if width > 0 && height > 0 => {
+                        Some((width, height))
+                    }
+                    _ => None,
+                };
+                match dimensions {
+                    Some(dimensions) => options.window = Some(dimensions),
+                    None => return Err(format!("invalid window size {:?}", size)),
+                }
+            }
+            "--host" => {
+                let value = match args.peek() {
+                    Some(value) if !value.starts_with('-') => args.next(),
+                    _ => None,
+                };
+                let address = match value {
+                    None => SocketAddr::from(([0, 0, 0, 0], DEFAULT_PORT)),
+                    Some(value) => match value.parse::<u16>() {
+                        Ok(port) => SocketAddr::from(([0, 0, 0, 0], port)),
+                        Err(_) => resolve(&value)?,
+                    },
+                };
+                options.net = NetMode::Host(address);
+            }
+            "--connect" => {
+                let value = args
+                    .next()
+                    .ok_or_else(|| "--connect needs an address".to_string())?;
+                let address = if value.contains(':') {
+                    resolve(&value)?
+                } else {
+                    resolve(&format!("{}:{}", value, DEFAULT_PORT))?
+                };
+                options.net = NetMode::Client(address);
+            }
+            "--rules" => {
+                options.rules = args
+                    .next()
+                    .ok_or_else(|| "--rules needs a preset".to_string())?;
+            }
+            "--record" | "--replay" => {
+                let path = args
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or_else(|| format!("{} needs a file", arg))?;
+                if arg == "--record" {
+                    options.record = Some(path);
+                } else {
+                    options.replay = Some(path);
+                }
+            }
+            _ => return Err(format!("unknown argument {:?}", arg)),
+        }
+    }
+    let online = match options.net {
+        NetMode::Local => false,
+        _ => true,
+    };
+    if options.headless && (online || options.replay.is_some()) {
+        return Err("--headless only plays local matches".to_string());
+    }
+    match (&options.net, &options.record, &options.replay) {
+        (NetMode::Client(_), Some(_), _) => {
+            Err("clients cannot record, only the host can".to_string())
+        }
+        (NetMode::Local, _, Some(_)) => Ok(options),
+        (_, _, Some(_)) => Err("a replay cannot be watched online".to_string()),
+        _ => Ok(options),
+    }
+}
+
+/// Checks that `humans` and `bots` make a match on a map with `spawn_points`,
+/// the humans sharing the keyboard unless `headless`.
+pub fn check_players(
+    humans: u8,
+    bots: u8,
+    spawn_points: usize,
+    headless: bool,
+) -> Result<(), String> {
+    let players = usize::from(humans) + usize::from(bots);
+    if players < usize::from(MIN_PLAYERS) {
+        Err(format!("a match needs at least {} players", MIN_PLAYERS))
+    } else if players > spawn_points {
+        Err(format!(
+            "{} players, but the map only has {} spawn points",
+            players, spawn_points
+        ))
+    } else if !headless && humans > MAX_HUMAN_PLAYERS {
+        Err(format!(
+            "at most {} humans can share the keyboard",
+            MAX_HUMAN_PLAYERS
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Parses the value given to `arg`.
+fn value<T>(arg: &str, value: Option<String>) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let value = value.ok_or_else(|| format!("{} needs a value", arg))?;
+    value
+        .parse()
+        .map_err(|err| format!("invalid value {:?} for {}: {}", value, arg, err))
+}
+
+fn resolve(address: &str) -> Result<SocketAddr, String> {
+    address
+        .to_socket_addrs()
+        .ok()
+        .and_then(|mut addresses| addresses.next())
+        .ok_or_else(|| format!("invalid address {:?}", address))
+}
@@ -1,42 +1,472 @@
-use std::fs::File;
-use std::io::{self, prelude::*, BufReader};
-
-use crate::state::{MapTiles, Tile, TileStatus};
-
-pub fn read_map(filepath: &str) -> io::Result<MapTiles> {
-    let file = File::open(filepath)?;
-    let reader = BufReader::new(file);
-
-    let mut map: MapTiles = [[Tile {
-        status: TileStatus::Free,
-        coordinates: [0, 0],
-    }; 11]; 13];
-    for (j, line) in reader.lines().enumerate() {
-        let line = line.unwrap();
-        for (i, c) in line.chars().enumerate() {
-            match c {
-                '-' => {
-                    map[i][j] = Tile {
-                        status: TileStatus::Free,
-                        coordinates: [i, j],
-                    }
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::sim::power_up::{DropRates, PowerUpKind};
+use crate::sim::{Map, TileStatus};
+
+#[derive(Debug)]
+pub enum MapError {
+    Io(io::Error),
+    /// `line` and `column` both start at 1.
+    Parse {
+        line: usize,
+        column: usize,
+        kind: ParseErrorKind,
+    },
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseErrorKind {
+    UnknownSymbol(char),
+    UnknownKey(String),
+    InvalidValue {
+        key: String,
+        value: String,
+    },
+    DropRatesAboveOne,
+    RowLength {
+        expected: usize,
+        found: usize,
+    },
+    DuplicateSpawnPoint(u8),
+    /// Not a single free tile for the players to start on.
+    NoFreeTile,
+    Empty,
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::UnknownSymbol(c) => write!(f, "unknown symbol {:?}", c),
+            ParseErrorKind::UnknownKey(key) => write!(f, "unknown key {:?}", key),
+            ParseErrorKind::InvalidValue { key, value } => {
+                write!(f, "invalid value {:?} for {:?}", value, key)
+            }
+            ParseErrorKind::DropRatesAboveOne => write!(f, "drop rates add up to more than 1"),
+            ParseErrorKind::RowLength { expected, found } => write!(
+                f,
+                "row is {} tiles long, expected {} like the first one",
+                found, expected
+            ),
+            ParseErrorKind::DuplicateSpawnPoint(number) => {
+                write!(f, "spawn point {} is declared twice", number)
+            }
+            ParseErrorKind::NoFreeTile => write!(f, "no free tile for the players to start on"),
+            ParseErrorKind::Empty => write!(f, "the map has no tiles"),
+        }
+    }
+}
+
+impl fmt::Display for MapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MapError::Io(err) => write!(f, "{}", err),
+            MapError::Parse { line, column, kind } => {
+                write!(f, "line {}, column {}: {}", line, column, kind)
+            }
+        }
+    }
+}
+
+impl Error for MapError {}
+
+impl From<io::Error> for MapError {
+    fn from(err: io::Error) -> Self {
+        MapError::Io(err)
+    }
+}
+
+fn parse_error(index: usize, column: usize, kind: ParseErrorKind) -> MapError {
+    MapError::Parse {
+        line: index + 1,
+        column,
+        kind,
+    }
+}
+
+pub fn read_map<P: AsRef<Path>>(filepath: P) -> Result<Map, MapError> {
+    let source = fs::read_to_string(filepath)?;
+    parse_map(&source)
+}
+
+/// Parses a map file. It starts with optional `key: value` lines followed by
+/// the layout, one line per row of tiles, the first line being the bottom row.
+///
+/// The keys are `name`, `author`, `music`, the track in `resources/audio/music`
+/// played on the map, and the chance, from 0 to 1, of a destroyed wall
+/// dropping each power-up: `drop_bomb_up`, `drop_fire_up`, `drop_speed_up`,
+/// `drop_kick` and `drop_remote_detonator`.
+///
+/// The layout uses these symbols:
+///
+/// - `-` free tile
+/// - `0` wall, destroyed by explosions
+/// - `#` permanent wall
+/// - `1` to `9` free tile where that player starts
+/// - `b`, `f`, `s`, `k`, `r` free tile with a bomb up, fire up, speed up, kick
+///   or remote detonator power-up on it
+///
+/// Without spawn points the players start in the corners, or on the free
+/// tiles closest to them when the corners are walls.
+pub fn parse_map(source: &str) -> Result<Map, MapError> {
+    let mut lines = source.lines().enumerate().peekable();
+
+    let mut name = String::new();
+    let mut author = String::new();
+    let mut music = String::new();
+    let mut drop_rates = DropRates::default();
+    let mut last_drop_rate = None;
+    while let Some(&(index, line)) = lines.peek() {
+        if !line.trim().is_empty() {
+            let separator = match line.find(':') {
+                Some(separator) => separator,
+                None => break,
+            };
+            let key = line[..separator].trim();
+            let value = line[separator + 1..].trim();
+            let value_column = separator + 2 + line[separator + 1..].find(value).unwrap_or(0);
+            let drop_rate = match key {
+                "name" => {
+                    name = value.to_string();
+                    None
                 }
-                '#' => {
-                    map[i][j] = Tile {
-                        status: TileStatus::PermanentWall,
-                        coordinates: [i, j],
-                    }
+                "author" => {
+                    author = value.to_string();
+                    None
+                }
+                "music" => {
+                    music = value.to_string();
+                    None
                 }
-                '0' => {
-                    map[i][j] = Tile {
-                        status: TileStatus::Wall,
-                        coordinates: [i, j],
+                "drop_bomb_up" => Some(&mut drop_rates.bomb_up),
+                "drop_fire_up" => Some(&mut drop_rates.fire_up),
+                "drop_speed_up" => Some(&mut drop_rates.speed_up),
+                "drop_kick" => Some(&mut drop_rates.kick),
+                "drop_remote_detonator" => Some(&mut drop_rates.remote_detonator),
+                _ => {
+                    let column = line.find(key).unwrap_or(0) + 1;
+                    return Err(parse_error(
+                        index,
+                        column,
+                        ParseErrorKind::UnknownKey(key.to_string()),
+                    ));
+                }
+            };
+            if let Some(rate) = drop_rate {
+                *rate = match value.parse::<f32>() {
+                    Ok(rate) if rate >= 0. && rate <= 1. => rate,
+                    _ => {
+                        return Err(parse_error(
+                            index,
+                            value_column,
+                            ParseErrorKind::InvalidValue {
+                                key: key.to_string(),
+                                value: value.to_string(),
+                            },
+                        ))
                     }
+                };
+                last_drop_rate = Some((index, value_column));
+            }
+        }
+        lines.next();
+    }
+
+    if let Some((index, column)) = last_drop_rate {
+        if drop_rates.total() > 1. {
+            return Err(parse_error(
+                index,
+                column,
+                ParseErrorKind::DropRatesAboveOne,
+            ));
+        }
+    }
+
+    let mut rows: Vec<(usize, &str)> = lines.collect();
+    while rows.last().map_or(false, |(_, row)| row.trim().is_empty()) {
+        rows.pop();
+    }
+    let width = match rows.first() {
+        Some((_, row)) => row.chars().count(),
+        None => {
+            let index = source.lines().count();
+            return Err(parse_error(index, 1, ParseErrorKind::Empty));
+        }
+    };
+    if width == 0 {
+        return Err(parse_error(rows[0].0, 1, ParseErrorKind::Empty));
+    }
+
+    let mut map = Map::new(width, rows.len());
+    map.name = name;
+    map.author = author;
+    map.music = music;
+    map.drop_rates = drop_rates;
+    for (y, &(index, row)) in rows.iter().enumerate() {
+        let found = row.chars().count();
+        if found != width {
+            return Err(parse_error(
+                index,
+                found.min(width) + 1,
+                ParseErrorKind::RowLength {
+                    expected: width,
+                    found,
+                },
+            ));
+        }
+        for (x, c) in row.chars().enumerate() {
+            let status = match c {
+                '-' => TileStatus::Free,
+                '#' => TileStatus::PermanentWall,
+                '0' => TileStatus::Wall,
+                '1'..='9' => {
+                    let number = c as u8 - b'1';
+                    if map.spawn_points.iter().any(|(n, _)| *n == number) {
+                        return Err(parse_error(
+                            index,
+                            x + 1,
+                            ParseErrorKind::DuplicateSpawnPoint(number + 1),
+                        ));
+                    }
+                    map.spawn_points.push((number, [x, y]));
+                    TileStatus::Free
+                }
+                'b' | 'f' | 's' | 'k' | 'r' => {
+                    let kind = match c {
+                        'b' => PowerUpKind::BombUp,
+                        'f' => PowerUpKind::FireUp,
+                        's' => PowerUpKind::SpeedUp,
+                        'k' => PowerUpKind::Kick,
+                        _ => PowerUpKind::RemoteDetonator,
+                    };
+                    map.power_ups.push(([x, y], kind));
+                    TileStatus::Free
                 }
-                _ => panic!("unknown symbol {}", c),
+                _ => return Err(parse_error(index, x + 1, ParseErrorKind::UnknownSymbol(c))),
+            };
+            map.update_tile(x, y, status);
+        }
+    }
+
+    if map.spawn_points.is_empty() {
+        let (right, top) = (map.width() - 1, map.height() - 1);
+        for (number, corner) in [[0, 0], [right, 0], [0, top], [right, top]]
+            .iter()
+            .enumerate()
+        {
+            let distance = |[x, y]: [usize; 2]| {
+                (x as i64 - corner[0] as i64).abs() + (y as i64 - corner[1] as i64).abs()
+            };
+            let nearest = map
+                .tiles()
+                .filter(|tile| tile.status == TileStatus::Free)
+                .map(|tile| tile.coordinates)
+                .filter(|coordinates| map.spawn_points.iter().all(|(_, c)| c != coordinates))
+                .min_by_key(|coordinates| distance(*coordinates));
+            if let Some(coordinates) = nearest {
+                map.spawn_points.push((number as u8, coordinates));
             }
         }
+        if map.spawn_points.is_empty() {
+            return Err(parse_error(rows[0].0, 1, ParseErrorKind::NoFreeTile));
+        }
     }
+    map.spawn_points.sort_by_key(|(number, _)| *number);
 
     Ok(map)
 }
+
+/// Writes `map` in the format read by `parse_map`.
+pub fn format_map(map: &Map) -> String {
+    let mut source = String::new();
+    for (key, value) in &[
+        ("name", &map.name),
+        ("author", &map.author),
+        ("music", &map.music),
+    ] {
+        if !value.is_empty() {
+            source.push_str(&format!("{}: {}\n", key, value));
+        }
+    }
+    let drop_rates = &map.drop_rates;
+    if *drop_rates != DropRates::default() {
+        for (key, rate) in &[
+            ("drop_bomb_up", drop_rates.bomb_up),
+            ("drop_fire_up", drop_rates.fire_up),
+            ("drop_speed_up", drop_rates.speed_up),
+            ("drop_kick", drop_rates.kick),
+            ("drop_remote_detonator", drop_rates.remote_detonator),
+        ] {
+            source.push_str(&format!("{}: {}\n", key, rate));
+        }
+    }
+    if !source.is_empty() {
+        source.push('\n');
+    }
+    for y in 0..map.height() {
+        for x in 0..map.width() {
+            let spawn_point = map
+                .spawn_points
+                .iter()
+                .find(|(_, coordinates)| *coordinates == [x, y]);
+            let power_up = map
+                .power_ups
+                .iter()
+                .find(|(coordinates, _)| *coordinates == [x, y]);
+            let symbol = match (map.get_tile_by_key(x, y).status, spawn_point, power_up) {
+                (TileStatus::Free, Some((number, _)), _) => (b'1' + number) as char,
+                (TileStatus::Free, None, Some((_, kind))) => match kind {
+                    PowerUpKind::BombUp => 'b',
+                    PowerUpKind::FireUp => 'f',
+                    PowerUpKind::SpeedUp => 's',
+                    PowerUpKind::Kick => 'k',
+                    PowerUpKind::RemoteDetonator => 'r',
+                },
+                (TileStatus::Free, None, None) => '-',
+                (TileStatus::Wall, _, _) => '0',
+                (TileStatus::PermanentWall, _, _) => '#',
+            };
+            source.push(symbol);
+        }
+        source.push('\n');
+    }
+    source
+}
+
+pub fn write_map<P: AsRef<Path>>(filepath: P, map: &Map) -> Result<(), MapError> {
+    fs::write(filepath, format_map(map))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Where and why `source` fails to parse.
+    fn parse_failure(source: &str) -> (usize, usize, ParseErrorKind) {
+        match parse_map(source) {
+            Err(MapError::Parse { line, column, kind }) => (line, column, kind),
+            Err(err) => panic!("unexpected error: {}", err),
+            Ok(_) => panic!("parsed {:?}", source),
+        }
+    }
+
+    #[test]
+    fn parses_a_map() {
+        let map = parse_map(
+            "name: Arena\n\
+             author: Someone\n\
+             music: battle\n\
+             drop_kick: 0.5\n\
+             \n\
+             1-0b\n\
+             -#f-\n\
+             r-k2\n",
+        )
+        .unwrap();
+        assert_eq!((map.width(), map.height()), (4, 3));
+        assert_eq!(map.name, "Arena");
+        assert_eq!(map.author, "Someone");
+        assert_eq!(map.music, "battle");
+        assert_eq!(map.drop_rates.kick, 0.5);
+        assert_eq!(map.drop_rates.bomb_up, DropRates::default().bomb_up);
+        // the first row is the bottom one
+        assert_eq!(map.spawn_points, vec![(0, [0, 0]), (1, [3, 2])]);
+        assert_eq!(map.get_tile_by_key(2, 0).status, TileStatus::Wall);
+        assert_eq!(map.get_tile_by_key(1, 1).status, TileStatus::PermanentWall);
+        assert_eq!(map.get_tile_by_key(3, 2).status, TileStatus::Free);
+        assert_eq!(
+            map.power_ups,
+            vec![
+                ([3, 0], PowerUpKind::BombUp),
+                ([2, 1], PowerUpKind::FireUp),
+                ([0, 2], PowerUpKind::RemoteDetonator),
+                ([2, 2], PowerUpKind::Kick),
+            ]
+        );
+    }
+
+    #[test]
+    fn players_start_in_the_corners_without_spawn_points() {
+        let map = parse_map("---\n---\n").unwrap();
+        assert_eq!(
+            map.spawn_points,
+            vec![(0, [0, 0]), (1, [2, 0]), (2, [0, 1]), (3, [2, 1])]
+        );
+        // or next to them when they are walls
+        let map = parse_map("#--\n---\n--#\n").unwrap();
+        assert_eq!(map.spawn_points[0], (0, [0, 1]));
+        assert_eq!(map.spawn_points[3], (3, [1, 2]));
+    }
+
+    #[test]
+    fn reports_where_and_why_maps_are_invalid() {
+        assert_eq!(
+            parse_failure("---\n-x-\n"),
+            (2, 2, ParseErrorKind::UnknownSymbol('x'))
+        );
+        assert_eq!(
+            parse_failure("colour: red\n\n---\n"),
+            (1, 1, ParseErrorKind::UnknownKey("colour".to_string()))
+        );
+        assert_eq!(
+            parse_failure("name: A\ndrop_kick: lots\n\n---\n"),
+            (
+                2,
+                12,
+                ParseErrorKind::InvalidValue {
+                    key: "drop_kick".to_string(),
+                    value: "lots".to_string(),
+                }
+            )
+        );
+        assert_eq!(
+            parse_failure("drop_kick: 0.6\ndrop_fire_up: 0.5\n\n---\n"),
+            (2, 15, ParseErrorKind::DropRatesAboveOne)
+        );
+        assert_eq!(
+            parse_failure("---\n--\n"),
+            (
+                2,
+                3,
+                ParseErrorKind::RowLength {
+                    expected: 3,
+                    found: 2,
+                }
+            )
+        );
+        assert_eq!(
+            parse_failure("-1-\n--1\n"),
+            (2, 3, ParseErrorKind::DuplicateSpawnPoint(1))
+        );
+        assert_eq!(
+            parse_failure("##\n##\n"),
+            (1, 1, ParseErrorKind::NoFreeTile)
+        );
+        assert_eq!(parse_failure("name: A\n"), (2, 1, ParseErrorKind::Empty));
+    }
+
+    #[test]
+    fn missing_files_are_io_errors() {
+        match read_map("resources/maps/does_not_exist.txt") {
+            Err(MapError::Io(_)) => {}
+            _ => panic!("expected an I/O error"),
+        }
+    }
+
+    #[test]
+    fn formatted_maps_parse_back_the_same() {
+        let mut map = parse_map("1-0b\n-#f-\nr-k2\n").unwrap();
+        map.name = "Arena".to_string();
+        map.author = "Someone".to_string();
+        map.music = "battle".to_string();
+        map.drop_rates.fire_up = 0.25;
+        assert_eq!(parse_map(&format_map(&map)).unwrap(), map);
+
+        let map = parse_map("---\n-#-\n---\n").unwrap();
+        assert_eq!(parse_map(&format_map(&map)).unwrap(), map);
+    }
+}
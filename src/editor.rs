@@ -0,0 +1,388 @@
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    core::math::Vector3,
+    core::transform::Transform,
+    ecs::{Entity, Join},
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    prelude::*,
+    renderer::SpriteRender,
+    ui::{get_default_font, Anchor, FontAsset, UiText, UiTransform},
+    window::ScreenDimensions,
+};
+
+use log::info;
+
+use std::path::Path;
+
+use crate::config::{read_map, write_map, MapError};
+use crate::entities::player::MAX_HUMAN_PLAYERS;
+use crate::entities::power_up;
+use crate::entities::tile::{self, Tile};
+use crate::sim::generator::{self, GeneratorSettings};
+use crate::sim::map::{TILE_HEIGHT, TILE_HEIGHT_HALF, TILE_WIDTH, TILE_WIDTH_HALF};
+use crate::sim::{Map, TileStatus};
+use crate::state::{
+    init_camera, init_sprites_map, load_sprites, AssetType, GameplayState, MatchSettings,
+    SpriteSheetList,
+};
+
+/// Size of a new map, the one of the classic arena.
+const NEW_MAP_WIDTH: usize = 13;
+const NEW_MAP_HEIGHT: usize = 11;
+
+/// One spawn point for each player the game has bindings, tints and a HUD
+/// slot for.
+const MAX_SPAWN_POINTS: u8 = MAX_HUMAN_PLAYERS;
+
+const HELP: &str = "Arrows move  F/W/P free, wall, pillar  1-4 spawn  G generate  T test  S save";
+
+/// Paints the tiles and spawn points of a map with a keyboard cursor, plays it
+/// and saves it back to its file.
+pub struct MapEditorState {
+    path: String,
+    map: Map,
+    cursor: [usize; 2],
+    /// The cursor, then the spawn points and power-ups drawn over the tiles.
+    markers: Vec<Entity>,
+    labels: Vec<Entity>,
+    message: String,
+}
+
+impl MapEditorState {
+    /// Edits the map saved at `path`.
+    pub fn open(path: &str) -> Result<Self, MapError> {
+        let map = read_map(path)?;
+        Ok(MapEditorState::new(path.to_string(), map))
+    }
+
+    /// Starts an empty map with a player in each corner, saved next to the
+    /// other maps under a name not taken yet.
+    pub fn create() -> Self {
+        let mut map = Map::new(NEW_MAP_WIDTH, NEW_MAP_HEIGHT);
+        let (right, top) = (NEW_MAP_WIDTH - 1, NEW_MAP_HEIGHT - 1);
+        map.spawn_points = vec![
+            (0, [0, 0]),
+            (1, [right, 0]),
+            (2, [0, top]),
+            (3, [right, top]),
+        ];
+        MapEditorState::new(new_map_path(), map)
+    }
+
+    /// Starts from a generated map, saved like a new one.
+    pub fn generate(settings: &GeneratorSettings, seed: u64) -> Self {
+        let map = generator::generate(settings, seed);
+        MapEditorState::new(new_map_path(), map)
+    }
+
+    /// Replaces the map with a generated one of the same size, give or take
+    /// a tile: generated maps have odd sizes.
+    fn regenerate(&mut self) {
+        let seed = rand::random();
+        let settings = GeneratorSettings {
+            width: self.map.width(),
+            height: self.map.height(),
+            ..GeneratorSettings::default()
+        };
+        self.map = generator::generate(&settings, seed);
+        self.message = format!("Generated from the seed {}", seed);
+    }
+
+    fn new(path: String, map: Map) -> Self {
+        MapEditorState {
+            path,
+            map,
+            cursor: [0, 0],
+            markers: Vec::new(),
+            labels: Vec::new(),
+            message: String::new(),
+        }
+    }
+
+    fn paint(&mut self, status: TileStatus) {
+        let [x, y] = self.cursor;
+        self.map.update_tile(x, y, status);
+        // only free tiles can hold a spawn point or a power-up
+        if status != TileStatus::Free {
+            let cursor = self.cursor;
+            self.map
+                .spawn_points
+                .retain(|(_, coordinates)| *coordinates != cursor);
+            self.map
+                .power_ups
+                .retain(|(coordinates, _)| *coordinates != cursor);
+        }
+    }
+
+    /// Moves the spawn point of player `number` under the cursor, or removes
+    /// it when it already is there.
+    fn toggle_spawn_point(&mut self, number: u8) {
+        let cursor = self.cursor;
+        let here = self
+            .map
+            .spawn_points
+            .iter()
+            .any(|&(n, coordinates)| n == number && coordinates == cursor);
+        self.map
+            .spawn_points
+            .retain(|&(n, coordinates)| n != number && coordinates != cursor);
+        if !here {
+            self.map.update_tile(cursor[0], cursor[1], TileStatus::Free);
+            self.map.spawn_points.push((number, cursor));
+            self.map.spawn_points.sort_by_key(|(number, _)| *number);
+        }
+    }
+
+    fn move_cursor(&mut self, dx: i32, dy: i32) {
+        let x = (self.cursor[0] as i32 + dx)
+            .max(0)
+            .min(self.map.width() as i32 - 1);
+        let y = (self.cursor[1] as i32 + dy)
+            .max(0)
+            .min(self.map.height() as i32 - 1);
+        self.cursor = [x as usize, y as usize];
+    }
+
+    fn save(&mut self) {
+        self.message = match write_map(&self.path, &self.map) {
+            Ok(()) => {
+                info!("saved the map to {}", self.path);
+                format!("Saved to {}", self.path)
+            }
+            Err(err) => format!("Failed to save {}: {}", self.path, err),
+        };
+    }
+
+    /// Builds the whole editor, the tiles being drawn by `init_sprites_map`
+    /// like in a game.
+    fn show(&mut self, world: &mut World) {
+        let sprite_sheet_list = load_sprites(world);
+        world.insert(sprite_sheet_list);
+        let dimensions = (*world.read_resource::<ScreenDimensions>()).clone();
+        init_camera(world, &self.map, &dimensions);
+        let sprites = tile_sprites(world);
+        init_sprites_map(world, &self.map, &sprites, &dimensions);
+
+        let font = {
+            let loader = world.read_resource::<Loader>();
+            let font_storage = world.read_resource::<AssetStorage<FontAsset>>();
+            get_default_font(&loader, &font_storage)
+        };
+        for (i, anchor) in [Anchor::TopMiddle, Anchor::BottomMiddle].iter().enumerate() {
+            let y = if i == 0 { -15. } else { 15. };
+            let transform = UiTransform::new(
+                format!("editor_{}", i),
+                *anchor,
+                *anchor,
+                0.,
+                y,
+                1.,
+                600.,
+                30.,
+            );
+            let text = UiText::new(font.clone(), String::new(), [1., 1., 1., 1.], 14.);
+            let label = world.create_entity().with(transform).with(text).build();
+            self.labels.push(label);
+        }
+        self.refresh(world);
+    }
+
+    /// Builds the whole editor again, for the tiles and the camera to fit a
+    /// map of another size.
+    fn rebuild(&mut self, world: &mut World) {
+        world.delete_all();
+        self.markers.clear();
+        self.labels.clear();
+        self.show(world);
+    }
+
+    /// Redraws the tiles, the markers and the labels after a change.
+    fn refresh(&mut self, world: &mut World) {
+        {
+            let tiles = world.read_storage::<Tile>();
+            let mut sprite_renders = world.write_storage::<SpriteRender>();
+            for (tile, sprite_render) in (&tiles, &mut sprite_renders).join() {
+                let [x, y] = tile.coordinates;
+                sprite_render.sprite_number =
+                    tile::sprite_number(self.map.get_tile_by_key(x, y).status);
+            }
+        }
+
+        world
+            .delete_entities(&self.markers)
+            .expect("failed to delete the markers");
+        self.markers.clear();
+        let sprites = tile_sprites(world);
+        // the cursor is a bomb, the spawn points are players
+        let cursor = world
+            .create_entity()
+            .with(sprites[3].clone())
+            .with(marker_transform(self.cursor, 0.5))
+            .build();
+        self.markers.push(cursor);
+        for &(_, coordinates) in &self.map.spawn_points {
+            let marker = world
+                .create_entity()
+                .with(sprites[2].clone())
+                .with(marker_transform(coordinates, 0.4))
+                .build();
+            self.markers.push(marker);
+        }
+        let sprite_sheet_list = (*world.read_resource::<SpriteSheetList>()).clone();
+        for &(coordinates, kind) in &self.map.power_ups {
+            let marker =
+                power_up::create_power_up_sprite(world, &sprite_sheet_list, kind, coordinates);
+            self.markers.push(marker);
+        }
+
+        let spawn_points = self
+            .map
+            .spawn_points
+            .iter()
+            .map(|(number, [x, y])| format!("P{} {},{}", number + 1, x, y))
+            .collect::<Vec<_>>()
+            .join("  ");
+        let status = if self.message.is_empty() {
+            format!("{}  {}", self.path, spawn_points)
+        } else {
+            self.message.clone()
+        };
+        let mut texts = world.write_storage::<UiText>();
+        for (label, line) in self.labels.iter().zip(&[HELP.to_string(), status]) {
+            if let Some(text) = texts.get_mut(*label) {
+                text.text = line.clone();
+            }
+        }
+    }
+
+    fn test_play(&mut self) -> SimpleTrans {
+        let mut map = self.map.clone();
+        if map.spawn_points.is_empty() {
+            self.message = "Place a spawn point to play the map".to_string();
+            return Trans::None;
+        }
+        // players are numbered after the spawn points placed, so that the
+        // human, player 1, gets the first one whatever its digit
+        map.spawn_points.sort_by_key(|(number, _)| *number);
+        map.spawn_points.truncate(MAX_SPAWN_POINTS as usize);
+        for (number, spawn_point) in map.spawn_points.iter_mut().enumerate() {
+            spawn_point.0 = number as u8;
+        }
+        let settings = MatchSettings {
+            map_path: self.path.clone(),
+            humans: 1,
+            bots: map.spawn_points.len() as u8 - 1,
+            rounds_to_win: 1,
+            ..MatchSettings::default()
+        };
+        Trans::Push(Box::new(GameplayState::with_map(settings, map)))
+    }
+}
+
+/// A file of `resources/maps` not taken yet.
+fn new_map_path() -> String {
+    (1..)
+        .map(|i| format!("resources/maps/custom_{}.txt", i))
+        .find(|path| !Path::new(path).exists())
+        .unwrap()
+}
+
+/// The sprites of `general.ron` the tiles are drawn with.
+fn tile_sprites(world: &World) -> Vec<SpriteRender> {
+    let sprite_sheet_list = world.read_resource::<SpriteSheetList>();
+    (0..5)
+        .map(|i| SpriteRender {
+            sprite_sheet: sprite_sheet_list.get(AssetType::Bomb).unwrap().clone(),
+            sprite_number: i,
+        })
+        .collect()
+}
+
+fn marker_transform(coordinates: [usize; 2], z: f32) -> Transform {
+    let mut transform = Transform::default();
+    transform.set_translation_xyz(
+        coordinates[0] as f32 * TILE_WIDTH + TILE_WIDTH_HALF,
+        coordinates[1] as f32 * TILE_HEIGHT + TILE_HEIGHT_HALF,
+        z,
+    );
+    transform.set_scale(Vector3::new(0.75, 0.75, 1.0));
+    transform
+}
+
+impl SimpleState for MapEditorState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.show(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        // the test game wiped everything on its way out
+        self.markers.clear();
+        self.labels.clear();
+        self.show(data.world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        data.world.delete_all();
+        self.markers.clear();
+        self.labels.clear();
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(&event) {
+                return Trans::Quit;
+            }
+            if is_key_down(&event, VirtualKeyCode::Escape) {
+                return Trans::Pop;
+            }
+            if is_key_down(&event, VirtualKeyCode::T) {
+                return self.test_play();
+            }
+            let digits = [
+                VirtualKeyCode::Key1,
+                VirtualKeyCode::Key2,
+                VirtualKeyCode::Key3,
+                VirtualKeyCode::Key4,
+            ];
+            let message = std::mem::take(&mut self.message);
+            if is_key_down(&event, VirtualKeyCode::Left) {
+                self.move_cursor(-1, 0);
+            } else if is_key_down(&event, VirtualKeyCode::Right) {
+                self.move_cursor(1, 0);
+            } else if is_key_down(&event, VirtualKeyCode::Up) {
+                self.move_cursor(0, 1);
+            } else if is_key_down(&event, VirtualKeyCode::Down) {
+                self.move_cursor(0, -1);
+            } else if is_key_down(&event, VirtualKeyCode::F) {
+                self.paint(TileStatus::Free);
+            } else if is_key_down(&event, VirtualKeyCode::W) {
+                self.paint(TileStatus::Wall);
+            } else if is_key_down(&event, VirtualKeyCode::P) {
+                self.paint(TileStatus::PermanentWall);
+            } else if is_key_down(&event, VirtualKeyCode::S) {
+                self.save();
+            } else if is_key_down(&event, VirtualKeyCode::G) {
+                let size = (self.map.width(), self.map.height());
+                self.regenerate();
+                if (self.map.width(), self.map.height()) != size {
+                    self.rebuild(data.world);
+                    return Trans::None;
+                }
+            } else if let Some(number) =
+                (0..MAX_SPAWN_POINTS).find(|number| is_key_down(&event, digits[*number as usize]))
+            {
+                self.toggle_spawn_point(number);
+            } else {
+                self.message = message;
+                return Trans::None;
+            }
+            self.refresh(data.world);
+        }
+
+        Trans::None
+    }
+}
@@ -0,0 +1,154 @@
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+
+use serde::{Deserialize, Serialize};
+
+use crate::sim::FlamePiece;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum AnimationMode {
+    /// Starts over from the first frame once the last one is over.
+    Loop,
+    /// Stays on the last frame.
+    Once,
+}
+
+impl Default for AnimationMode {
+    fn default() -> Self {
+        AnimationMode::Loop
+    }
+}
+
+/// Sprites shown one after the other, each for `frame_duration` seconds of
+/// game time.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct AnimationData {
+    pub frames: Vec<usize>,
+    pub frame_duration: f32,
+    pub mode: AnimationMode,
+}
+
+impl AnimationData {
+    pub fn duration(&self) -> f32 {
+        self.frames.len() as f32 * self.frame_duration
+    }
+
+    /// The same frames, played over `duration` seconds.
+    pub fn stretched(&self, duration: f32) -> AnimationData {
+        let mut data = self.clone();
+        if !data.frames.is_empty() {
+            data.frame_duration = duration / data.frames.len() as f32;
+        }
+        data
+    }
+}
+
+/// Every animation of the game, read from `resources/animations.ron`. The
+/// frames of the flames are sprites of `explosion.ron`, the others of
+/// `general.ron`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Animations {
+    pub walk_left: AnimationData,
+    pub walk_right: AnimationData,
+    pub walk_up: AnimationData,
+    pub walk_down: AnimationData,
+    pub death: AnimationData,
+    pub bomb_fuse: AnimationData,
+    pub flame_center: AnimationData,
+    pub flame_horizontal: AnimationData,
+    pub flame_vertical: AnimationData,
+    pub flame_up_end: AnimationData,
+    pub flame_right_end: AnimationData,
+    pub flame_down_end: AnimationData,
+    pub flame_left_end: AnimationData,
+    pub wall_crumbling: AnimationData,
+}
+
+impl Animations {
+    pub fn flame(&self, piece: FlamePiece) -> &AnimationData {
+        match piece {
+            FlamePiece::Center => &self.flame_center,
+            FlamePiece::Horizontal => &self.flame_horizontal,
+            FlamePiece::Vertical => &self.flame_vertical,
+            FlamePiece::UpEnd => &self.flame_up_end,
+            FlamePiece::RightEnd => &self.flame_right_end,
+            FlamePiece::DownEnd => &self.flame_down_end,
+            FlamePiece::LeftEnd => &self.flame_left_end,
+        }
+    }
+}
+
+/// Sets the sprite of the entity from the game time elapsed since it started.
+pub struct Animation {
+    pub data: AnimationData,
+    elapsed: f32,
+    /// Stays on the current frame while set.
+    pub stopped: bool,
+    /// Deletes the entity once an animation played once is over.
+    pub remove_when_done: bool,
+}
+
+impl Component for Animation {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Animation {
+    pub fn new(data: &AnimationData) -> Self {
+        Animation {
+            data: data.clone(),
+            elapsed: 0.0,
+            stopped: false,
+            remove_when_done: false,
+        }
+    }
+
+    /// Plays the animation once, then deletes the entity.
+    pub fn once_then_remove(data: &AnimationData) -> Self {
+        let mut animation = Animation::new(data);
+        animation.data.mode = AnimationMode::Once;
+        animation.remove_when_done = true;
+        animation
+    }
+
+    /// Starts `data` over, unless it is already playing.
+    pub fn play(&mut self, data: &AnimationData) {
+        if self.data != *data {
+            self.data = data.clone();
+            self.elapsed = 0.0;
+        }
+        self.stopped = false;
+    }
+
+    /// Goes back to the first frame and stays there.
+    pub fn stop(&mut self) {
+        self.elapsed = 0.0;
+        self.stopped = true;
+    }
+
+    pub fn advance(&mut self, seconds: f32) {
+        if !self.stopped {
+            self.elapsed += seconds;
+        }
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.data.mode == AnimationMode::Once && self.elapsed >= self.data.duration()
+    }
+
+    /// Sprite to show at this point of the animation, none without frames.
+    pub fn sprite_number(&self) -> Option<usize> {
+        let len = self.data.frames.len();
+        if len == 0 {
+            return None;
+        }
+        let frame = if self.data.frame_duration > 0.0 {
+            (self.elapsed / self.data.frame_duration) as usize
+        } else {
+            0
+        };
+        let frame = match self.data.mode {
+            AnimationMode::Loop => frame % len,
+            AnimationMode::Once => frame.min(len - 1),
+        };
+        Some(self.data.frames[frame])
+    }
+}
@@ -1,41 +1,42 @@
 use amethyst::core::math::Vector3;
-use amethyst::core::timing::Stopwatch;
 use amethyst::core::transform::Transform;
 use amethyst::ecs::prelude::{Component, DenseVecStorage};
-use amethyst::ecs::{Entities, LazyUpdate};
+use amethyst::ecs::{Entities, WriteStorage};
 use amethyst::renderer::SpriteRender;
 
-use crate::state::{
-    AssetType, Map, SpriteSheetList, ARENA_HEIGHT, ARENA_WIDTH, TILE_COUNT_HORIZONTAL,
-    TILE_COUNT_VERTICAL, TILE_HEIGHT_HALF, TILE_WIDTH_HALF,
-};
-use std::time::Duration;
+use crate::entities::animation::{Animation, Animations};
+use crate::sim::map::{TILE_HEIGHT, TILE_HEIGHT_HALF, TILE_WIDTH, TILE_WIDTH_HALF};
+use crate::state::{AssetType, SpriteSheetList};
 
+/// Links a bomb sprite to the `sim::Bomb` with the same id.
 pub struct Bomb {
-    pub created_time: Duration,
-    pub power: u8,
-    pub player_number: u8,
+    pub id: u32,
 }
 
 impl Component for Bomb {
     type Storage = DenseVecStorage<Self>;
 }
 
+/// Shows a bomb just placed. Its components are added right away, not
+/// through the `LazyUpdate`, so the bomb can be found to be moved or blown up
+/// within the same frame.
 pub fn spawn_bomb(
     entities: &Entities,
-    transform: &Transform,
-    lazy_update: &LazyUpdate,
+    (bombs, sprite_renders, animation_storage, transforms): (
+        &mut WriteStorage<Bomb>,
+        &mut WriteStorage<SpriteRender>,
+        &mut WriteStorage<Animation>,
+        &mut WriteStorage<Transform>,
+    ),
     sprite_sheet_list: &SpriteSheetList,
-    map: &Map,
-    gametime: &Stopwatch,
-    player_number: u8,
+    animations: &Animations,
+    id: u32,
+    coordinates: [usize; 2],
 ) {
-    let bomb_entity = entities.create();
     let mut bomb_transform = Transform::default();
-    let tile = map.get_tile(transform.translation().x, transform.translation().y);
     bomb_transform.set_translation_xyz(
-        tile.coordinates[0] as f32 * (ARENA_WIDTH / TILE_COUNT_HORIZONTAL) + TILE_WIDTH_HALF,
-        tile.coordinates[1] as f32 * (ARENA_HEIGHT / TILE_COUNT_VERTICAL) + TILE_HEIGHT_HALF,
+        coordinates[0] as f32 * TILE_WIDTH + TILE_WIDTH_HALF,
+        coordinates[1] as f32 * TILE_HEIGHT + TILE_HEIGHT_HALF,
         0.2,
     );
     bomb_transform.set_scale(Vector3::new(0.75, 0.75, 1.0));
@@ -43,14 +44,11 @@ pub fn spawn_bomb(
         sprite_sheet: sprite_sheet_list.get(AssetType::Bomb).unwrap().clone(),
         sprite_number: 3,
     };
-    lazy_update.insert(bomb_entity, sprite_render);
-    lazy_update.insert(
-        bomb_entity,
-        Bomb {
-            created_time: gametime.elapsed(),
-            power: 1,
-            player_number,
-        },
-    );
-    lazy_update.insert(bomb_entity, bomb_transform);
+    entities
+        .build_entity()
+        .with(sprite_render, sprite_renders)
+        .with(Animation::new(&animations.bomb_fuse), animation_storage)
+        .with(Bomb { id }, bombs)
+        .with(bomb_transform, transforms)
+        .build();
 }
@@ -0,0 +1,144 @@
+use amethyst::{
+    assets::{AssetStorage, Loader},
+    core::transform::Transform,
+    ecs::prelude::{Component, DenseVecStorage},
+    prelude::*,
+    renderer::SpriteRender,
+    ui::{get_default_font, Anchor, FontAsset, UiText, UiTransform},
+    window::ScreenDimensions,
+};
+
+use crate::entities::player::team_tint;
+use crate::sim::Simulation;
+
+/// Height of the strip above the arena the HUD is drawn in, in the same units
+/// as the tiles.
+pub const HUD_HEIGHT: f32 = 40.0;
+/// The clock takes the top of the strip, the players the rest.
+const CLOCK_HEIGHT: f32 = 12.0;
+const LINE_HEIGHT: f32 = 14.0;
+const PORTRAIT_WIDTH: f32 = 20.0;
+
+/// A text of the HUD, kept up to date by the `HudSystem`.
+pub enum HudLabel {
+    Clock,
+    /// Round wins of the side of that player.
+    Wins(u8),
+    /// Bombs, fire and speed of that player.
+    PowerUps(u8),
+}
+
+impl Component for HudLabel {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// The picture of a player in the HUD.
+pub struct HudPortrait {
+    pub number: u8,
+}
+
+impl Component for HudPortrait {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Sets up the HUD above the arena for the players of the `Simulation`,
+/// showing them with `sprite`. The labels are placed in fractions of the
+/// screen, so they stay in the strip whatever the size of the window.
+pub fn init_hud(world: &mut World, sprite: &SpriteRender) {
+    let font = {
+        let loader = world.read_resource::<Loader>();
+        let font_storage = world.read_resource::<AssetStorage<FontAsset>>();
+        get_default_font(&loader, &font_storage)
+    };
+    let (width, arena_height, players) = {
+        let simulation = world.read_resource::<Simulation>();
+        let players: Vec<(u8, Option<u8>)> = simulation
+            .players
+            .iter()
+            .map(|player| (player.number, player.team))
+            .collect();
+        (
+            simulation.map.arena_width(),
+            simulation.map.arena_height(),
+            players,
+        )
+    };
+    let height = arena_height + HUD_HEIGHT;
+    // pixels on screen per unit of the arena
+    let scale = world.read_resource::<ScreenDimensions>().height() / height;
+    // a label spanning `label_width` units centered on `x`, `top` units from
+    // the top of the screen
+    let label = |id: String, x: f32, top: f32, label_width: f32, label_height: f32| {
+        UiTransform::new(
+            id,
+            Anchor::TopLeft,
+            Anchor::Middle,
+            x / width,
+            -(top + label_height / 2.) / height,
+            1.,
+            label_width / width,
+            label_height / height,
+        )
+        .into_percent()
+    };
+
+    let clock = label("hud_clock".to_string(), width / 2., 0., width, CLOCK_HEIGHT);
+    let text = UiText::new(
+        font.clone(),
+        String::new(),
+        [1., 1., 1., 1.],
+        CLOCK_HEIGHT * scale,
+    );
+    world
+        .create_entity()
+        .with(clock)
+        .with(text)
+        .with(HudLabel::Clock)
+        .build();
+
+    let slot_width = width / players.len().max(1) as f32;
+    for (i, (number, team)) in players.into_iter().enumerate() {
+        let left = i as f32 * slot_width;
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(
+            left + PORTRAIT_WIDTH / 2.,
+            arena_height + (HUD_HEIGHT - CLOCK_HEIGHT) / 2.,
+            0.5,
+        );
+        let mut portrait = world
+            .create_entity()
+            .with(sprite.clone())
+            .with(HudPortrait { number })
+            .with(transform);
+        if let Some(team) = team {
+            portrait = portrait.with(team_tint(team));
+        }
+        portrait.build();
+
+        let text_width = slot_width - PORTRAIT_WIDTH;
+        let text_x = left + PORTRAIT_WIDTH + text_width / 2.;
+        let lines = vec![HudLabel::Wins(number), HudLabel::PowerUps(number)];
+        for (line, kind) in lines.into_iter().enumerate() {
+            let top = CLOCK_HEIGHT + line as f32 * LINE_HEIGHT;
+            let transform = label(
+                format!("hud_{}_{}", number, line),
+                text_x,
+                top,
+                text_width,
+                LINE_HEIGHT,
+            );
+            let text = UiText::new(
+                font.clone(),
+                String::new(),
+                [1., 1., 1., 1.],
+                LINE_HEIGHT * scale * 0.75,
+            );
+            world
+                .create_entity()
+                .with(transform)
+                .with(text)
+                .with(kind)
+                .build();
+        }
+    }
+}
@@ -1,3 +1,7 @@
+pub mod animation;
 pub mod bomb;
 pub mod explosion;
+pub mod hud;
 pub mod player;
+pub mod power_up;
+pub mod tile;
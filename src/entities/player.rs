@@ -2,53 +2,140 @@ use amethyst::core::math::Vector3;
 use amethyst::core::transform::Transform;
 use amethyst::ecs::prelude::{Component, DenseVecStorage};
 use amethyst::prelude::*;
-use amethyst::renderer::SpriteRender;
+use amethyst::renderer::{palette::Srgba, resources::Tint, SpriteRender};
 
-use crate::state::{ARENA_HEIGHT, ARENA_WIDTH};
+use std::collections::{HashMap, HashSet};
 
-pub const PLAYER_WIDTH: f32 = 12.0;
-pub const PLAYER_HEIGHT: f32 = 12.0;
+use crate::entities::animation::{Animation, Animations};
+use crate::sim::{PlayerInput, Simulation};
 
-pub const PLAYER_WIDTH_HALF: f32 = PLAYER_WIDTH / 2.0;
-pub const PLAYER_HEIGHT_HALF: f32 = PLAYER_HEIGHT / 2.0;
+/// Number of binding sets, `p1` to `p4`, in `bindings.ron`.
+pub const MAX_HUMAN_PLAYERS: u8 = 4;
 
+/// Tints of the players of each team, in team order.
+const TEAM_COLORS: [(f32, f32, f32); 4] = [
+    (1.0, 0.45, 0.45),
+    (0.45, 0.6, 1.0),
+    (0.5, 1.0, 0.5),
+    (1.0, 0.9, 0.4),
+];
+
+/// Number of teams the players can be split into.
+pub const MAX_TEAMS: u8 = TEAM_COLORS.len() as u8;
+
+/// Names of the axes and actions a human player is controlled with.
+pub struct BindingSet {
+    pub leftright: String,
+    pub updown: String,
+    pub fire: String,
+    pub detonate: String,
+}
+
+impl BindingSet {
+    fn new(prefix: &str) -> Self {
+        BindingSet {
+            leftright: format!("{}_leftright", prefix),
+            updown: format!("{}_updown", prefix),
+            fire: format!("{}_fire", prefix),
+            detonate: format!("{}_detonate", prefix),
+        }
+    }
+}
+
+/// Maps the number of every human player to its binding set, the others are
+/// left to the AI unless someone plays them over the network.
+#[derive(Default)]
+pub struct Controls {
+    binding_sets: HashMap<u8, BindingSet>,
+    remote: HashSet<u8>,
+}
+
+impl Controls {
+    /// Gives the first `humans` players the binding sets `p1`, `p2`...
+    pub fn new(humans: u8) -> Self {
+        let binding_sets = (0..humans.min(MAX_HUMAN_PLAYERS))
+            .map(|number| (number, BindingSet::new(&format!("p{}", number + 1))))
+            .collect();
+        Controls {
+            binding_sets,
+            remote: HashSet::new(),
+        }
+    }
+
+    /// Gives the binding set `p1` to the player `number`, which is the one a
+    /// client controls.
+    pub fn for_player(number: u8) -> Self {
+        let mut binding_sets = HashMap::new();
+        binding_sets.insert(number, BindingSet::new("p1"));
+        Controls {
+            binding_sets,
+            remote: HashSet::new(),
+        }
+    }
+
+    pub fn add_remote(&mut self, number: u8) {
+        self.remote.insert(number);
+    }
+
+    pub fn remove_remote(&mut self, number: u8) {
+        self.remote.remove(&number);
+    }
+
+    pub fn binding_set(&self, number: u8) -> Option<&BindingSet> {
+        self.binding_sets.get(&number)
+    }
+
+    pub fn is_human(&self, number: u8) -> bool {
+        self.binding_sets.contains_key(&number) || self.remote.contains(&number)
+    }
+}
+
+/// Links a player sprite to the `sim::Player` with the same number.
 pub struct Player {
     pub is_human: bool,
     pub number: u8,
-    pub num_bombs: u8,
 }
 
 impl Component for Player {
     type Storage = DenseVecStorage<Self>;
 }
 
+impl Component for PlayerInput {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Colours the sprites of the players of `team`.
+pub fn team_tint(team: u8) -> Tint {
+    let (red, green, blue) = TEAM_COLORS[team as usize % TEAM_COLORS.len()];
+    Tint(Srgba::new(red, green, blue, 1.0))
+}
+
 pub fn init_players(world: &mut World, sprites: &[SpriteRender]) {
-    for i in 0..4 {
-        let x = if i % 2 == 0 {
-            PLAYER_WIDTH_HALF
-        } else {
-            ARENA_WIDTH - PLAYER_WIDTH_HALF
-        };
-        let y = if i < 2 {
-            PLAYER_HEIGHT_HALF
-        } else {
-            ARENA_HEIGHT - PLAYER_HEIGHT_HALF
-        };
+    let positions: Vec<(u8, Option<u8>, f32, f32)> = world
+        .read_resource::<Simulation>()
+        .players
+        .iter()
+        .map(|player| (player.number, player.team, player.x, player.y))
+        .collect();
+    for (number, team, x, y) in positions {
         let mut transform = Transform::default();
         transform.set_translation_xyz(x, y, 0.4);
         transform.set_scale(Vector3::new(0.75, 0.75, 1.0));
 
-        let is_human = if i == 0 { true } else { false };
+        let is_human = world.read_resource::<Controls>().is_human(number);
+        let mut animation = Animation::new(&world.read_resource::<Animations>().walk_down);
+        animation.stop();
 
-        world
+        let mut builder = world
             .create_entity()
             .with(sprites[2].clone())
-            .with(Player {
-                is_human,
-                number: i,
-                num_bombs: 1,
-            })
-            .with(transform)
-            .build();
+            .with(Player { is_human, number })
+            .with(PlayerInput::default())
+            .with(animation)
+            .with(transform);
+        if let Some(team) = team {
+            builder = builder.with(team_tint(team));
+        }
+        builder.build();
     }
 }
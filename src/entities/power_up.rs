@@ -0,0 +1,99 @@
+use amethyst::core::math::Vector3;
+use amethyst::core::transform::Transform;
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+use amethyst::ecs::{Entities, Entity, WriteStorage};
+use amethyst::prelude::*;
+use amethyst::renderer::SpriteRender;
+
+use crate::sim::map::{TILE_HEIGHT, TILE_HEIGHT_HALF, TILE_WIDTH, TILE_WIDTH_HALF};
+use crate::sim::{PowerUpKind, Simulation};
+use crate::state::{AssetType, SpriteSheetList};
+
+/// Links a power-up sprite to the `sim::PowerUp` with the same id.
+pub struct PowerUp {
+    pub id: u32,
+}
+
+impl Component for PowerUp {
+    type Storage = DenseVecStorage<Self>;
+}
+
+fn sprite_render(sprite_sheet_list: &SpriteSheetList, kind: PowerUpKind) -> SpriteRender {
+    let sprite_number = match kind {
+        PowerUpKind::BombUp => 0,
+        PowerUpKind::FireUp => 1,
+        PowerUpKind::SpeedUp => 2,
+        PowerUpKind::Kick => 3,
+        PowerUpKind::RemoteDetonator => 4,
+    };
+    SpriteRender {
+        sprite_sheet: sprite_sheet_list.get(AssetType::PowerUp).unwrap().clone(),
+        sprite_number,
+    }
+}
+
+fn transform(coordinates: [usize; 2]) -> Transform {
+    let mut transform = Transform::default();
+    transform.set_translation_xyz(
+        coordinates[0] as f32 * TILE_WIDTH + TILE_WIDTH_HALF,
+        coordinates[1] as f32 * TILE_HEIGHT + TILE_HEIGHT_HALF,
+        0.15,
+    );
+    transform.set_scale(Vector3::new(0.75, 0.75, 1.0));
+    transform
+}
+
+/// Shows a power-up dropped during the round. Its components are added right
+/// away, not through the `LazyUpdate`, so the power-up can be found to be
+/// taken away again within the same frame.
+pub fn spawn_power_up(
+    entities: &Entities,
+    (power_ups, sprite_renders, transforms): (
+        &mut WriteStorage<PowerUp>,
+        &mut WriteStorage<SpriteRender>,
+        &mut WriteStorage<Transform>,
+    ),
+    sprite_sheet_list: &SpriteSheetList,
+    id: u32,
+    kind: PowerUpKind,
+    coordinates: [usize; 2],
+) {
+    entities
+        .build_entity()
+        .with(sprite_render(sprite_sheet_list, kind), sprite_renders)
+        .with(PowerUp { id }, power_ups)
+        .with(transform(coordinates), transforms)
+        .build();
+}
+
+/// Creates the power-ups the map starts with.
+pub fn init_power_ups(world: &mut World, sprite_sheet_list: &SpriteSheetList) {
+    let power_ups: Vec<(u32, PowerUpKind, [usize; 2])> = world
+        .read_resource::<Simulation>()
+        .power_ups
+        .iter()
+        .map(|power_up| (power_up.id, power_up.kind, power_up.coordinates))
+        .collect();
+    for (id, kind, coordinates) in power_ups {
+        world
+            .create_entity()
+            .with(sprite_render(sprite_sheet_list, kind))
+            .with(PowerUp { id })
+            .with(transform(coordinates))
+            .build();
+    }
+}
+
+/// Shows a power-up the map starts with, without any `sim::PowerUp` behind it.
+pub fn create_power_up_sprite(
+    world: &mut World,
+    sprite_sheet_list: &SpriteSheetList,
+    kind: PowerUpKind,
+    coordinates: [usize; 2],
+) -> Entity {
+    world
+        .create_entity()
+        .with(sprite_render(sprite_sheet_list, kind))
+        .with(transform(coordinates))
+        .build()
+}
@@ -0,0 +1,21 @@
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+
+use crate::sim::TileStatus;
+
+/// Links a tile sprite to the tile of the `Map` at `coordinates`.
+pub struct Tile {
+    pub coordinates: [usize; 2],
+}
+
+impl Component for Tile {
+    type Storage = DenseVecStorage<Self>;
+}
+
+/// Sprite of `general.ron` showing a tile in the given state.
+pub fn sprite_number(status: TileStatus) -> usize {
+    match status {
+        TileStatus::Wall => 4,
+        TileStatus::Free => 1,
+        TileStatus::PermanentWall => 0,
+    }
+}
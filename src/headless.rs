@@ -0,0 +1,143 @@
+//! Plays whole matches between bots on the `Simulation` alone, without a
+//! window, for matches to be run from scripts.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use std::collections::HashMap;
+
+use crate::config::{read_map, MapError};
+use crate::replay::ReplayRecorder;
+use crate::sim::generator;
+use crate::sim::{
+    ai, Difficulty, GameRules, Match, PlayerInput, RoundOutcome, Simulation, TICKS_PER_SECOND,
+};
+use crate::state::MatchSettings;
+use crate::stats::{MatchStats, STATS_DIR};
+
+/// A match still undecided after this many rounds is called off, should the
+/// bots keep drawing.
+const MAX_ROUNDS: u32 = 100;
+
+/// Plays the match `settings` describe with `rules`, every player, human or
+/// not, being a bot of `difficulty`. How every round went and the final
+/// scores are printed, the statistics are exported like after any match and
+/// the match is recorded into `recorder` when given. The players are expected
+/// to fit on the map, two of them at least.
+pub fn run(
+    settings: &MatchSettings,
+    rules: &GameRules,
+    difficulty: Difficulty,
+    mut recorder: Option<ReplayRecorder>,
+) -> Result<(), MapError> {
+    // rounds are drawn the same as in the `GameplayState`, and bots think on
+    // every tick there too, so a match between bots plays out the same from
+    // the same seed with or without a window
+    let seed = settings.seed.unwrap_or_else(rand::random);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let map = match &settings.generator {
+        Some(_) => None,
+        None => Some(read_map(&settings.map_path)?),
+    };
+    if let Some(recorder) = recorder.as_mut() {
+        recorder.replay.map_path = settings.map_path.clone();
+        recorder.replay.seed = seed;
+        recorder.replay.rounds_to_win = settings.rounds_to_win;
+        recorder.replay.teams = settings.teams;
+        recorder.replay.friendly_fire = settings.friendly_fire;
+        recorder.replay.rules = rules.clone();
+    }
+    println!("seed: {}", seed);
+
+    let mut game_match = Match::new(settings.rounds_to_win);
+    let mut match_stats = MatchStats::default();
+    let mut round = 0;
+    let winner = loop {
+        if game_match.rounds_played() >= MAX_ROUNDS {
+            break None;
+        }
+        let round_seed = rng.gen();
+        let mut map = match (&map, &settings.generator) {
+            (Some(map), _) => map.clone(),
+            (None, Some(generator)) => generator::generate(generator, round_seed),
+            (None, None) => unreachable!(),
+        };
+        let players = usize::from(settings.humans) + usize::from(settings.bots);
+        map.spawn_points.truncate(players);
+        if let Some(recorder) = recorder.as_mut() {
+            recorder.start_round(map.clone(), rules.round_ticks());
+        }
+        let mut simulation = Simulation::new(map, round_seed, rules.clone());
+        simulation.round = round;
+        simulation.assign_teams(settings.teams);
+        simulation.friendly_fire = settings.friendly_fire;
+
+        let outcome = loop {
+            if let Some(outcome) = simulation.outcome() {
+                break outcome;
+            }
+            let inputs: HashMap<u8, PlayerInput> = simulation
+                .players
+                .iter()
+                .map(|player| {
+                    let input = ai::think(&simulation, player.number, difficulty);
+                    (player.number, input)
+                })
+                .collect();
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.record(&inputs);
+            }
+            simulation.step(&inputs);
+        };
+
+        game_match.record(outcome);
+        match_stats.record(&simulation);
+        let seconds = simulation.tick() / TICKS_PER_SECOND;
+        let result = match outcome {
+            RoundOutcome::Winner(side) => format!("{} wins", side),
+            RoundOutcome::Draw => "draw".to_string(),
+        };
+        println!(
+            "round {}: {} after {}:{:02}",
+            round + 1,
+            result,
+            seconds / 60,
+            seconds % 60
+        );
+        if let Some(recorder) = &recorder {
+            if let Err(err) = recorder.save() {
+                eprintln!("failed to save the replay: {}", err);
+            }
+        }
+        if let Some(winner) = game_match.winner() {
+            break Some(winner);
+        }
+        round += 1;
+    };
+
+    match winner {
+        Some(winner) => println!("{} wins the match", winner),
+        None => println!("no winner after {} rounds", MAX_ROUNDS),
+    }
+    for (number, side) in &match_stats.sides {
+        let stats = match_stats
+            .stats
+            .players
+            .get(number)
+            .cloned()
+            .unwrap_or_default();
+        println!(
+            "Player {}: {} wins, {} kills, {} suicides, {} deaths",
+            number + 1,
+            game_match.wins(*side),
+            stats.kills,
+            stats.suicides,
+            stats.deaths
+        );
+    }
+    match match_stats.export(&game_match, STATS_DIR) {
+        Ok(path) => println!("statistics: {}", path.display()),
+        Err(err) => eprintln!("failed to save the match statistics: {}", err),
+    }
+    Ok(())
+}
@@ -1,4 +1,6 @@
 use amethyst::{
+    audio::{AudioBundle, DjSystemDesc},
+    config::Config,
     core::transform::TransformBundle,
     input::{InputBundle, StringBindings},
     prelude::*,
@@ -7,23 +9,137 @@ use amethyst::{
         types::DefaultBackend,
         RenderingBundle,
     },
+    ui::{RenderUi, UiBundle},
     utils::application_root_dir,
+    window::DisplayConfig,
 };
 
+use std::time::Duration;
+
+use crate::audio::{AudioSettings, Music};
+use crate::config::read_map;
+use crate::entities::animation::Animations;
+use crate::net::{NetClient, NetHost, NetMode};
+use crate::replay::{Replay, ReplayPlayer, ReplayRecorder};
+use crate::sim::generator::{self, GeneratorSettings};
+
+mod audio;
+mod cli;
 mod config;
+mod editor;
 mod entities;
+mod headless;
+mod net;
+mod replay;
+mod rules;
+mod sim;
 mod state;
+mod stats;
 mod systems;
 
+/// How long a client waits for the host to answer.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
 fn main() -> amethyst::Result<()> {
-    amethyst::start_logger(Default::default());
+    let options = match cli::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if options.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
 
     let app_root = application_root_dir()?;
+    let game_rules = match rules::load_preset(&options.rules) {
+        Ok(game_rules) => game_rules,
+        Err(err) => {
+            eprintln!(
+                "failed to load the rules {:?}: {}\navailable presets: {}",
+                options.rules,
+                err,
+                rules::presets().join(", ")
+            );
+            std::process::exit(2);
+        }
+    };
+
+    let mut settings = state::MatchSettings {
+        rules_preset: options.rules.clone(),
+        seed: options.seed,
+        ..state::MatchSettings::default()
+    };
+    let hosting = match options.net {
+        NetMode::Host(_) => true,
+        _ => false,
+    };
+    let spawn_points = match options.map.as_ref() {
+        Some(map) if map == cli::RANDOM_MAP => {
+            settings.generator = Some(GeneratorSettings::default());
+            generator::SPAWN_POINTS
+        }
+        map => {
+            if let Some(path) = map {
+                settings.map_path = path.clone();
+            }
+            match read_map(&settings.map_path) {
+                Ok(ref map) if hosting && !net::fits(map, &game_rules) => {
+                    eprintln!(
+                        "the map {} is too large to be played online",
+                        settings.map_path
+                    );
+                    std::process::exit(2);
+                }
+                Ok(map) => map.spawn_points.len(),
+                Err(err) => {
+                    eprintln!("failed to load the map {}: {}", settings.map_path, err);
+                    std::process::exit(2);
+                }
+            }
+        }
+    };
+    if let Some(humans) = options.humans {
+        settings.humans = humans;
+    }
+    if let Some(bots) = options.bots {
+        settings.bots = bots;
+    }
+    // the menu keeps the numbers within bounds, the command line is checked
+    // here; without a window nothing but the match itself can stop it
+    if options.headless || options.humans.is_some() || options.bots.is_some() {
+        let players = cli::check_players(
+            settings.humans,
+            settings.bots,
+            spawn_points,
+            options.headless,
+        );
+        if let Err(err) = players {
+            eprintln!("{}\n\n{}", err, cli::USAGE);
+            std::process::exit(2);
+        }
+    }
+
+    if options.headless {
+        let recorder = options.record.map(ReplayRecorder::new);
+        headless::run(&settings, &game_rules, options.difficulty, recorder)?;
+        return Ok(());
+    }
+
+    amethyst::start_logger(Default::default());
 
     let resources = app_root.join("resources");
-    let display_config = resources.join("display_config.ron");
+    let mut display_config = DisplayConfig::load_no_fallback(resources.join("display_config.ron"))?;
+    if let Some(dimensions) = options.window {
+        display_config.dimensions = Some(dimensions);
+    }
+    let animations = Animations::load_no_fallback(resources.join("animations.ron"))?;
 
     let binding_path = app_root.join("config").join("bindings.ron");
+    let mut audio_settings = AudioSettings::load(app_root.join("config").join("audio.ron"));
+    audio_settings.enabled &= !options.no_audio;
 
     let input_bundle =
         InputBundle::<StringBindings>::new().with_bindings_from_file(binding_path)?;
@@ -31,31 +147,121 @@ fn main() -> amethyst::Result<()> {
     let game_data = GameDataBuilder::default()
         .with_bundle(TransformBundle::new())?
         .with_bundle(input_bundle)?
+        .with_bundle(UiBundle::<StringBindings>::new())?
+        .with(
+            systems::PlayerInputSystem.pausable(state::CurrentState::Running),
+            "player_input_system",
+            &["input_system"],
+        );
+    let game_data = match options.net {
+        // clients mirror the host in place of the simulation, keeping its name
+        // for the systems below to depend on
+        NetMode::Client(_) => game_data.with(
+            systems::NetClientSystem,
+            "simulation_system",
+            &["player_input_system"],
+        ),
+        _ => game_data.with(
+            systems::SimulationSystem.pausable(state::CurrentState::Running),
+            "simulation_system",
+            &["player_input_system"],
+        ),
+    };
+    let game_data = match options.net {
+        NetMode::Host(_) => game_data.with(
+            systems::NetHostSystem,
+            "net_host_system",
+            &["simulation_system"],
+        ),
+        _ => game_data,
+    };
+    let game_data = if audio_settings.enabled {
+        game_data
+            .with_bundle(AudioBundle::default())?
+            .with_system_desc(
+                DjSystemDesc::new(|music: &mut Music| music.next()),
+                "dj_system",
+                &[],
+            )
+            .with(
+                systems::AudioSystem.pausable(state::CurrentState::Running),
+                "audio_system",
+                &["simulation_system"],
+            )
+    } else {
+        game_data
+    };
+    let game_data = game_data
         .with(
             systems::MovementSystem.pausable(state::CurrentState::Running),
             "movement_system",
-            &["input_system"],
+            &["simulation_system"],
         )
         .with(
             systems::ActionsSystem.pausable(state::CurrentState::Running),
             "actions_system",
-            &["input_system"],
+            &["simulation_system"],
         )
         .with(
             systems::ExplosionSystem.pausable(state::CurrentState::Running),
             "explosion_system",
-            &[],
+            // bombs placed and blown up in the same step are spawned first
+            &["simulation_system", "actions_system"],
+        )
+        .with(
+            systems::PowerUpSystem.pausable(state::CurrentState::Running),
+            "power_up_system",
+            &["simulation_system"],
+        )
+        .with(
+            systems::TileSystem.pausable(state::CurrentState::Running),
+            "tile_system",
+            &["simulation_system"],
+        )
+        .with(
+            systems::AnimationSystem.pausable(state::CurrentState::Running),
+            "animation_system",
+            &[
+                "movement_system",
+                "actions_system",
+                "explosion_system",
+                "tile_system",
+            ],
         )
+        .with(systems::HudSystem, "hud_system", &["simulation_system"])
         .with_bundle(
             RenderingBundle::<DefaultBackend>::new()
                 .with_plugin(
-                    RenderToWindow::from_config_path(display_config)
-                        .with_clear([0.34, 0.36, 0.52, 1.0]),
+                    RenderToWindow::from_config(display_config).with_clear([0.34, 0.36, 0.52, 1.0]),
                 )
-                .with_plugin(RenderFlat2D::default()),
+                .with_plugin(RenderFlat2D::default())
+                .with_plugin(RenderUi::default()),
         )?;
 
-    let mut game = Application::new(resources, state::GameplayState {}, game_data)?;
+    let mut game = Application::build(resources, state::MainMenuState::new(settings))?;
+    match options.net {
+        NetMode::Local => {}
+        NetMode::Host(address) => game = game.with_resource(NetHost::bind(address)?),
+        NetMode::Client(address) => {
+            game = game.with_resource(NetClient::connect(address, CONNECT_TIMEOUT)?)
+        }
+    }
+    if let Some(path) = options.replay {
+        let replay = Replay::load(path)?;
+        game = game.with_resource(ReplayPlayer::new(replay));
+    }
+    if audio_settings.enabled {
+        game = game.with_resource(Music::default());
+    }
+    game = game
+        .with_resource(audio_settings)
+        .with_resource(animations)
+        .with_resource(game_rules)
+        .with_resource(options.difficulty);
+    if let Some(path) = options.record {
+        game = game.with_resource(ReplayRecorder::new(path));
+    }
+    let mut game = game.build(game_data)?;
     game.run();
 
     Ok(())
@@ -0,0 +1,166 @@
+use log::{info, warn};
+
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use super::protocol::{decode, encode, ClientMessage, ServerMessage, MAX_DATAGRAM_SIZE};
+use crate::sim::{PlayerInput, Snapshot};
+
+/// How long to wait for an answer before asking the host again.
+const RETRY_INTERVAL: Duration = Duration::from_millis(250);
+
+pub struct NetClient {
+    socket: UdpSocket,
+    number: u8,
+    rounds_to_win: u8,
+    frame: u32,
+    snapshot_frame: u32,
+    snapshot: Option<Snapshot>,
+    applied: bool,
+    buffer: Vec<u8>,
+}
+
+impl NetClient {
+    /// Joins the game hosted at `address`, waiting up to `timeout` for the
+    /// host to give us a player and a first snapshot.
+    pub fn connect<A: ToSocketAddrs>(address: A, timeout: Duration) -> io::Result<Self> {
+        let address = address.to_socket_addrs()?.next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "no address to connect to")
+        })?;
+        let socket = if address.is_ipv4() {
+            UdpSocket::bind("0.0.0.0:0")?
+        } else {
+            UdpSocket::bind("[::]:0")?
+        };
+        socket.connect(address)?;
+        socket.set_read_timeout(Some(RETRY_INTERVAL))?;
+
+        let mut client = NetClient {
+            socket,
+            number: 0,
+            rounds_to_win: 0,
+            frame: 0,
+            snapshot_frame: 0,
+            snapshot: None,
+            applied: false,
+            buffer: vec![0; MAX_DATAGRAM_SIZE],
+        };
+        let start = Instant::now();
+        let mut number = None;
+        while number.is_none() || client.snapshot.is_none() {
+            if start.elapsed() > timeout {
+                return Err(io::Error::new(
+                    io::ErrorKind::TimedOut,
+                    format!("no answer from {}", address),
+                ));
+            }
+            if number.is_none() {
+                client.send(&ClientMessage::Join);
+            }
+            let length = match client.socket.recv(&mut client.buffer) {
+                Ok(length) => length,
+                Err(ref err)
+                    if err.kind() == io::ErrorKind::WouldBlock
+                        || err.kind() == io::ErrorKind::TimedOut =>
+                {
+                    continue
+                }
+                Err(err) => return Err(err),
+            };
+            match decode(&client.buffer[..length]) {
+                Some(ServerMessage::Welcome {
+                    number: welcome,
+                    rounds_to_win,
+                }) => {
+                    number = Some(welcome);
+                    client.rounds_to_win = rounds_to_win;
+                }
+                Some(ServerMessage::Full) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::Other,
+                        format!("the game at {} is full", address),
+                    ))
+                }
+                Some(ServerMessage::Snapshot { frame, snapshot }) if number.is_some() => {
+                    client.keep(frame, snapshot)
+                }
+                _ => {}
+            }
+        }
+        client.number = number.unwrap();
+        client.socket.set_nonblocking(true)?;
+        info!("joined {} as player {}", address, client.number);
+        Ok(client)
+    }
+
+    /// The player we control.
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+
+    /// Wins the host set for taking the match.
+    pub fn rounds_to_win(&self) -> u8 {
+        self.rounds_to_win
+    }
+
+    pub fn send_input(&mut self, input: PlayerInput) {
+        self.frame += 1;
+        let frame = self.frame;
+        self.send(&ClientMessage::Input { frame, input });
+    }
+
+    /// Reads every snapshot received since the last call, keeping the most
+    /// recent one.
+    pub fn receive(&mut self) {
+        loop {
+            let length = match self.socket.recv(&mut self.buffer) {
+                Ok(length) => length,
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    warn!("failed to receive: {}", err);
+                    break;
+                }
+            };
+            if let Some(ServerMessage::Snapshot { frame, snapshot }) =
+                decode(&self.buffer[..length])
+            {
+                self.keep(frame, snapshot);
+            }
+        }
+    }
+
+    /// The most recent snapshot of the host.
+    pub fn snapshot(&self) -> Option<&Snapshot> {
+        self.snapshot.as_ref()
+    }
+
+    /// The most recent snapshot, unless it was already taken.
+    pub fn take_update(&mut self) -> Option<Snapshot> {
+        if self.applied {
+            return None;
+        }
+        self.applied = true;
+        self.snapshot.clone()
+    }
+
+    fn keep(&mut self, frame: u32, snapshot: Snapshot) {
+        if frame > self.snapshot_frame {
+            self.snapshot_frame = frame;
+            self.snapshot = Some(snapshot);
+            self.applied = false;
+        }
+    }
+
+    fn send(&self, message: &ClientMessage) {
+        if let Err(err) = self.socket.send(&encode(message)) {
+            warn!("failed to send to the host: {}", err);
+        }
+    }
+}
+
+impl Drop for NetClient {
+    fn drop(&mut self) {
+        self.send(&ClientMessage::Leave);
+    }
+}
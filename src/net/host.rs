@@ -0,0 +1,175 @@
+use log::{info, warn};
+
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs, UdpSocket};
+use std::time::{Duration, Instant};
+
+use super::protocol::{decode, encode, ClientMessage, ServerMessage, MAX_DATAGRAM_SIZE};
+use crate::sim::{PlayerInput, Snapshot};
+
+/// Clients not heard of for this long are considered gone.
+const PEER_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub enum HostEvent {
+    Joined(u8),
+    Left(u8),
+    Input(u8, PlayerInput),
+}
+
+struct Peer {
+    address: SocketAddr,
+    number: u8,
+    last_frame: u32,
+    last_seen: Instant,
+}
+
+pub struct NetHost {
+    /// Told to clients when they join, so they know when the match is over.
+    pub rounds_to_win: u8,
+    socket: UdpSocket,
+    peers: Vec<Peer>,
+    frame: u32,
+    buffer: Vec<u8>,
+}
+
+impl NetHost {
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        let host = NetHost {
+            rounds_to_win: 1,
+            socket,
+            peers: Vec::new(),
+            frame: 0,
+            buffer: vec![0; MAX_DATAGRAM_SIZE],
+        };
+        info!("hosting on {}", host.local_addr()?);
+        Ok(host)
+    }
+
+    /// The address clients join, with the port picked when binding to 0.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Handles every message received since the last call. A client joining
+    /// takes over the first player of `free_numbers` no other client has.
+    pub fn receive(&mut self, free_numbers: &[u8]) -> Vec<HostEvent> {
+        let mut events = Vec::new();
+        loop {
+            let (length, address) = match self.socket.recv_from(&mut self.buffer) {
+                Ok(received) => received,
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    warn!("failed to receive: {}", err);
+                    break;
+                }
+            };
+            let message = match decode(&self.buffer[..length]) {
+                Some(message) => message,
+                None => continue,
+            };
+            self.handle(address, message, free_numbers, &mut events);
+        }
+
+        let now = Instant::now();
+        let (gone, peers): (Vec<Peer>, Vec<Peer>) = self
+            .peers
+            .drain(..)
+            .partition(|peer| now - peer.last_seen > PEER_TIMEOUT);
+        self.peers = peers;
+        for peer in gone {
+            info!("player {} timed out", peer.number);
+            events.push(HostEvent::Left(peer.number));
+        }
+        events
+    }
+
+    fn handle(
+        &mut self,
+        address: SocketAddr,
+        message: ClientMessage,
+        free_numbers: &[u8],
+        events: &mut Vec<HostEvent>,
+    ) {
+        let index = self.peers.iter().position(|peer| peer.address == address);
+        match (message, index) {
+            (ClientMessage::Join, Some(index)) => {
+                // the welcome got lost on the way
+                let number = self.peers[index].number;
+                self.welcome(address, number);
+            }
+            (ClientMessage::Join, None) => {
+                let peers = &self.peers;
+                let free = free_numbers
+                    .iter()
+                    .find(|number| peers.iter().all(|peer| peer.number != **number));
+                match free {
+                    Some(&number) => {
+                        info!("{} joined as player {}", address, number);
+                        self.peers.push(Peer {
+                            address,
+                            number,
+                            last_frame: 0,
+                            last_seen: Instant::now(),
+                        });
+                        self.welcome(address, number);
+                        events.push(HostEvent::Joined(number));
+                    }
+                    None => self.send(address, &ServerMessage::Full),
+                }
+            }
+            (ClientMessage::Input { frame, input }, Some(index)) => {
+                let peer = &mut self.peers[index];
+                peer.last_seen = Instant::now();
+                if frame > peer.last_frame {
+                    peer.last_frame = frame;
+                    events.push(HostEvent::Input(peer.number, input));
+                }
+            }
+            (ClientMessage::Leave, Some(index)) => {
+                let peer = self.peers.remove(index);
+                info!("player {} left", peer.number);
+                events.push(HostEvent::Left(peer.number));
+            }
+            (_, None) => {}
+        }
+    }
+
+    pub fn broadcast(&mut self, snapshot: Snapshot) {
+        if self.peers.is_empty() {
+            return;
+        }
+        self.frame += 1;
+        let bytes = encode(&ServerMessage::Snapshot {
+            frame: self.frame,
+            snapshot,
+        });
+        if bytes.len() > MAX_DATAGRAM_SIZE {
+            warn!("the snapshot takes {} bytes, too many to send", bytes.len());
+            return;
+        }
+        for peer in &self.peers {
+            if let Err(err) = self.socket.send_to(&bytes, peer.address) {
+                warn!("failed to send a snapshot to {}: {}", peer.address, err);
+            }
+        }
+    }
+
+    fn welcome(&self, address: SocketAddr, number: u8) {
+        let rounds_to_win = self.rounds_to_win;
+        self.send(
+            address,
+            &ServerMessage::Welcome {
+                number,
+                rounds_to_win,
+            },
+        );
+    }
+
+    fn send(&self, address: SocketAddr, message: &ServerMessage) {
+        if let Err(err) = self.socket.send_to(&encode(message), address) {
+            warn!("failed to send to {}: {}", address, err);
+        }
+    }
+}
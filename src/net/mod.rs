@@ -0,0 +1,149 @@
+//! Online multiplayer over UDP. The host runs the `Simulation` and sends a
+//! `Snapshot` of it to every client after each step, the clients only send
+//! the input of their player and mirror the snapshots they receive.
+//!
+//! None of it depends on amethyst, so a host and its clients can just as well
+//! live in the same process.
+
+use std::net::SocketAddr;
+
+pub use self::client::NetClient;
+pub use self::host::{HostEvent, NetHost};
+
+use self::protocol::{encode, ServerMessage, MAX_DATAGRAM_SIZE};
+use crate::sim::{
+    Bomb, Explosion, Flame, FlamePiece, GameRules, Map, PowerUp, PowerUpKind, Simulation,
+};
+
+mod client;
+mod host;
+pub mod protocol;
+
+pub const DEFAULT_PORT: u16 = 7777;
+
+pub enum NetMode {
+    Local,
+    Host(SocketAddr),
+    Client(SocketAddr),
+}
+
+/// Whether the snapshots of a round on `map` played with `rules` fit in a
+/// datagram, even with flames and a power-up on every tile and the bombs of
+/// every player down. Larger maps cannot be played online.
+pub fn fits(map: &Map, rules: &GameRules) -> bool {
+    let mut simulation = Simulation::new(map.clone(), 0, rules.clone());
+    let tiles: Vec<[usize; 2]> = map.tiles().map(|tile| tile.coordinates).collect();
+    let bombs = map.spawn_points.len() * usize::from(rules.max_bombs);
+    simulation.bombs = (0..bombs)
+        .map(|id| Bomb {
+            id: id as u32,
+            coordinates: [0, 0],
+            created_tick: 0,
+            power: rules.max_power,
+            player_number: 0,
+            remote: false,
+            sliding: None,
+            moved_tick: 0,
+            owner_on_top: false,
+        })
+        .collect();
+    simulation.explosions = (0..bombs.max(1))
+        .map(|id| Explosion {
+            id: id as u32,
+            created_tick: 0,
+            player_number: 0,
+            flames: Vec::new(),
+        })
+        .collect();
+    simulation.explosions[0].flames = tiles
+        .iter()
+        .map(|&coordinates| Flame {
+            coordinates,
+            piece: FlamePiece::Center,
+        })
+        .collect();
+    simulation.power_ups = tiles
+        .iter()
+        .map(|&coordinates| PowerUp {
+            id: 0,
+            coordinates,
+            kind: PowerUpKind::BombUp,
+        })
+        .collect();
+    let message = ServerMessage::Snapshot {
+        frame: 0,
+        snapshot: simulation.snapshot(),
+    };
+    encode(&message).len() <= MAX_DATAGRAM_SIZE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::PlayerInput;
+
+    use std::collections::HashMap;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    #[test]
+    fn clients_get_snapshots_of_their_inputs() {
+        let mut map = Map::new(5, 1);
+        map.spawn_points = vec![(0, [0, 0]), (1, [3, 0])];
+        let start_x = Simulation::new(map.clone(), 0, GameRules::default()).players[1].x;
+        let mut host = NetHost::bind("127.0.0.1:0").unwrap();
+        let address = host.local_addr().unwrap();
+
+        // the host steps until a little after the first input of player 1
+        let host_thread = thread::spawn(move || {
+            let mut simulation = Simulation::new(map, 0, GameRules::default());
+            let mut inputs = HashMap::new();
+            let mut steps_left = 30;
+            let start = Instant::now();
+            while steps_left > 0 && start.elapsed() < TIMEOUT {
+                for event in host.receive(&[1]) {
+                    if let HostEvent::Input(number, input) = event {
+                        inputs.insert(number, input);
+                    }
+                }
+                if !inputs.is_empty() {
+                    steps_left -= 1;
+                }
+                simulation.step(&inputs);
+                host.broadcast(simulation.snapshot());
+                thread::sleep(Duration::from_millis(2));
+            }
+            inputs
+        });
+
+        let mut client = NetClient::connect(address, TIMEOUT).unwrap();
+        assert_eq!(client.number(), 1);
+        assert!(client.take_update().is_some());
+        client.send_input(PlayerInput {
+            movement_x: -1.,
+            ..PlayerInput::default()
+        });
+        let start = Instant::now();
+        let mut moved = false;
+        while !moved && start.elapsed() < TIMEOUT {
+            client.receive();
+            if let Some(snapshot) = client.take_update() {
+                moved = snapshot.players[1].x < start_x;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+
+        let inputs = host_thread.join().unwrap();
+        assert_eq!(inputs.get(&1).map(|input| input.movement_x), Some(-1.));
+        assert!(moved);
+    }
+
+    #[test]
+    fn large_maps_do_not_fit_in_a_snapshot() {
+        let rules = GameRules::default();
+        assert!(fits(&Map::new(13, 11), &rules));
+        assert!(!fits(&Map::new(100, 100), &rules));
+    }
+}
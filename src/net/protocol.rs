@@ -0,0 +1,42 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::sim::{PlayerInput, Snapshot};
+
+/// Largest payload a UDP datagram can carry.
+pub const MAX_DATAGRAM_SIZE: usize = 65_507;
+
+#[derive(Serialize, Deserialize)]
+pub enum ClientMessage {
+    Join,
+    /// `frame` grows with every input sent, so the host can drop the ones
+    /// arriving out of order.
+    Input {
+        frame: u32,
+        input: PlayerInput,
+    },
+    Leave,
+}
+
+#[derive(Serialize, Deserialize)]
+pub enum ServerMessage {
+    Welcome {
+        number: u8,
+        rounds_to_win: u8,
+    },
+    /// Every player is already taken.
+    Full,
+    Snapshot {
+        frame: u32,
+        snapshot: Snapshot,
+    },
+}
+
+pub fn encode<T: Serialize>(message: &T) -> Vec<u8> {
+    bincode::serialize(message).expect("failed to encode a message")
+}
+
+/// Returns `None` for anything which is not a message of ours.
+pub fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Option<T> {
+    bincode::deserialize(bytes).ok()
+}
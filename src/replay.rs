@@ -0,0 +1,296 @@
+use log::info;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::sim::{GameRules, Map, PlayerInput};
+
+/// Speed of the playback while fast-forwarding.
+const FAST_FORWARD: u32 = 4;
+
+#[derive(Debug)]
+pub enum ReplayError {
+    Io(io::Error),
+    Format(bincode::Error),
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReplayError::Io(err) => write!(f, "{}", err),
+            ReplayError::Format(err) => write!(f, "not a replay: {}", err),
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+impl From<io::Error> for ReplayError {
+    fn from(err: io::Error) -> Self {
+        ReplayError::Io(err)
+    }
+}
+
+impl From<bincode::Error> for ReplayError {
+    fn from(err: bincode::Error) -> Self {
+        ReplayError::Format(err)
+    }
+}
+
+/// Everything needed to play a match again: the seed the rounds were drawn
+/// from, the rules and the teams, then the map, the tick limit and the inputs of every
+/// player on every tick of each round.
+#[derive(Default, Serialize, Deserialize)]
+pub struct Replay {
+    pub map_path: String,
+    pub seed: u64,
+    pub rounds_to_win: u8,
+    pub teams: u8,
+    pub friendly_fire: bool,
+    pub rules: GameRules,
+    pub rounds: Vec<ReplayRound>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ReplayRound {
+    pub map: Map,
+    pub tick_limit: u32,
+    pub ticks: Vec<BTreeMap<u8, PlayerInput>>,
+}
+
+impl Replay {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ReplayError> {
+        // decoding from memory checks every length against what is left,
+        // where a reader would first allocate whatever a broken file claims
+        let bytes = fs::read(path)?;
+        Ok(bincode::deserialize(&bytes)?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), ReplayError> {
+        let writer = BufWriter::new(File::create(path)?);
+        Ok(bincode::serialize_into(writer, self)?)
+    }
+}
+
+/// Writes down the inputs the `Simulation` is stepped with.
+pub struct ReplayRecorder {
+    path: PathBuf,
+    pub replay: Replay,
+}
+
+impl ReplayRecorder {
+    pub fn new<P: Into<PathBuf>>(path: P) -> Self {
+        ReplayRecorder {
+            path: path.into(),
+            replay: Replay::default(),
+        }
+    }
+
+    pub fn start_round(&mut self, map: Map, tick_limit: u32) {
+        self.replay.rounds.push(ReplayRound {
+            map,
+            tick_limit,
+            ticks: Vec::new(),
+        });
+    }
+
+    /// Forgets the inputs of the round being played, which starts over.
+    pub fn restart_round(&mut self) {
+        if let Some(round) = self.replay.rounds.last_mut() {
+            round.ticks.clear();
+        }
+    }
+
+    pub fn record(&mut self, inputs: &HashMap<u8, PlayerInput>) {
+        if let Some(round) = self.replay.rounds.last_mut() {
+            round.ticks.push(
+                inputs
+                    .iter()
+                    .map(|(number, input)| (*number, *input))
+                    .collect(),
+            );
+        }
+    }
+
+    /// Writes everything recorded so far, the file is rewritten every time.
+    pub fn save(&self) -> Result<(), ReplayError> {
+        self.replay.save(&self.path)?;
+        info!("saved the replay to {}", self.path.display());
+        Ok(())
+    }
+}
+
+/// Feeds the inputs of a `Replay` back to the `Simulation`, one tick after the
+/// other, at the pace chosen by the viewer.
+pub struct ReplayPlayer {
+    pub replay: Replay,
+    /// Index of the round being played, none before the first one.
+    round: Option<usize>,
+    tick: usize,
+    pub paused: bool,
+    pub fast_forward: bool,
+    step: bool,
+}
+
+impl ReplayPlayer {
+    pub fn new(replay: Replay) -> Self {
+        ReplayPlayer {
+            replay,
+            round: None,
+            tick: 0,
+            paused: false,
+            fast_forward: false,
+            step: false,
+        }
+    }
+
+    /// Moves on to the next round, returning the map it is played on and its
+    /// tick limit.
+    pub fn next_round(&mut self) -> Option<(Map, u32)> {
+        let round = self.round.map_or(0, |round| round + 1);
+        let next = self.replay.rounds.get(round)?;
+        let next = (next.map.clone(), next.tick_limit);
+        self.round = Some(round);
+        self.tick = 0;
+        Some(next)
+    }
+
+    /// Whether the round being played is the last one recorded.
+    pub fn is_last_round(&self) -> bool {
+        self.round.map_or(0, |round| round + 1) >= self.replay.rounds.len()
+    }
+
+    /// The inputs of the next tick, until the round runs out of them.
+    pub fn next_inputs(&mut self) -> Option<HashMap<u8, PlayerInput>> {
+        let round = &self.replay.rounds[self.round?];
+        let inputs = round.ticks.get(self.tick)?;
+        self.tick += 1;
+        Some(
+            inputs
+                .iter()
+                .map(|(number, input)| (*number, *input))
+                .collect(),
+        )
+    }
+
+    /// How many ticks to play per tick of game time, 0 while paused.
+    pub fn speed(&self) -> u32 {
+        match (self.paused, self.fast_forward) {
+            (true, _) => 0,
+            (false, true) => FAST_FORWARD,
+            (false, false) => 1,
+        }
+    }
+
+    /// Plays a single tick on the next frame, meant to be used while paused.
+    pub fn step(&mut self) {
+        self.step = true;
+    }
+
+    /// Whether a single tick was asked for since the last call.
+    pub fn take_step(&mut self) -> bool {
+        std::mem::replace(&mut self.step, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A file of the temporary directory for this test alone.
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("replay_{}_{}.bin", std::process::id(), name))
+    }
+
+    fn input(movement_x: f32, fire: bool) -> PlayerInput {
+        PlayerInput {
+            movement_x,
+            fire,
+            ..PlayerInput::default()
+        }
+    }
+
+    #[test]
+    fn replays_play_back_what_was_recorded() {
+        let path = temp_path("round_trip");
+        let mut map = Map::new(5, 3);
+        map.spawn_points = vec![(0, [0, 0]), (1, [4, 2])];
+        let rules = GameRules {
+            fuse_time: 1.5,
+            ..GameRules::default()
+        };
+        let ticks: Vec<HashMap<u8, PlayerInput>> = vec![
+            vec![(0, input(1., false)), (1, input(-1., false))],
+            vec![(0, input(0., true)), (1, input(-1., false))],
+            vec![(0, input(-1., false))],
+        ]
+        .into_iter()
+        .map(|tick| tick.into_iter().collect())
+        .collect();
+
+        let mut recorder = ReplayRecorder::new(&path);
+        recorder.replay.map_path = "resources/maps/default.txt".to_string();
+        recorder.replay.seed = 42;
+        recorder.replay.rounds_to_win = 3;
+        recorder.replay.rules = rules.clone();
+        recorder.start_round(map.clone(), 600);
+        for inputs in &ticks {
+            recorder.record(inputs);
+        }
+        recorder.save().unwrap();
+
+        let replay = Replay::load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(replay.map_path, "resources/maps/default.txt");
+        assert_eq!(replay.seed, 42);
+        assert_eq!(replay.rounds_to_win, 3);
+        assert_eq!(replay.rules, rules);
+
+        let mut player = ReplayPlayer::new(replay);
+        assert_eq!(player.next_round(), Some((map, 600)));
+        assert!(player.is_last_round());
+        for inputs in &ticks {
+            let played = player.next_inputs().unwrap();
+            assert_eq!(played.len(), inputs.len());
+            for (number, input) in inputs {
+                assert_eq!(played[number].movement_x, input.movement_x);
+                assert_eq!(played[number].fire, input.fire);
+            }
+        }
+        assert!(player.next_inputs().is_none());
+        assert!(player.next_round().is_none());
+    }
+
+    #[test]
+    fn truncated_or_foreign_files_are_not_replays() {
+        let mut recorder = ReplayRecorder::new(temp_path("truncated"));
+        recorder.start_round(Map::new(13, 11), 600);
+        recorder.record(&HashMap::new());
+        let bytes = bincode::serialize(&recorder.replay).unwrap();
+
+        for (name, contents) in &[
+            ("truncated", &bytes[..bytes.len() / 2]),
+            ("foreign", &b"name: Default\n1-2\n"[..]),
+        ] {
+            let path = temp_path(name);
+            fs::write(&path, contents).unwrap();
+            let result = Replay::load(&path);
+            fs::remove_file(&path).unwrap();
+            match result {
+                Err(ReplayError::Format(_)) => {}
+                _ => panic!("the {} file loaded", name),
+            }
+        }
+
+        match Replay::load(temp_path("missing")) {
+            Err(ReplayError::Io(_)) => {}
+            _ => panic!("a missing file loaded"),
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use amethyst::config::{Config, ConfigError};
+use amethyst::utils::application_root_dir;
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::sim::GameRules;
+
+/// Where the presets of `GameRules` are read from, one `<name>.ron` each,
+/// under the application root.
+pub const RULES_DIR: &str = "config/rules";
+pub const DEFAULT_PRESET: &str = "classic";
+
+/// `RULES_DIR` under the application root, so that the presets are found
+/// wherever the game is run from.
+fn rules_dir() -> PathBuf {
+    application_root_dir()
+        .map(|root| root.join(RULES_DIR))
+        .unwrap_or_else(|_| Path::new(RULES_DIR).to_path_buf())
+}
+
+pub fn preset_path(name: &str) -> PathBuf {
+    rules_dir().join(format!("{}.ron", name))
+}
+
+pub fn load_preset(name: &str) -> Result<GameRules, ConfigError> {
+    GameRules::load_no_fallback(preset_path(name))
+}
+
+/// The name of every preset of `RULES_DIR`, sorted.
+pub fn presets() -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(rules_dir())
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().map_or(false, |ext| ext == "ron"))
+                .filter_map(|path| {
+                    path.file_stem()
+                        .map(|stem| stem.to_string_lossy().into_owned())
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    names.sort();
+    names
+}
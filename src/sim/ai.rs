@@ -0,0 +1,333 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::str::FromStr;
+
+use super::map::{Map, TileStatus, TILE_HEIGHT, TILE_HEIGHT_HALF, TILE_WIDTH, TILE_WIDTH_HALF};
+use super::player::PlayerInput;
+use super::{Simulation, TICKS_PER_SECOND};
+
+// how far a bot may drift from the middle of its lane before it corrects itself,
+// otherwise it gets caught on the corners of the walls
+const ALIGNMENT_TOLERANCE: f32 = 1.5;
+
+type Coordinates = [usize; 2];
+type Grid<T> = Vec<Vec<T>>;
+
+/// One `value` per tile of `map`, indexed by `[x][y]`.
+fn grid<T: Clone>(map: &Map, value: T) -> Grid<T> {
+    vec![vec![value; map.height()]; map.width()]
+}
+
+/// How well the bots play. Every level runs from bombs the same way, they
+/// differ in how often they stop to think, how far ahead they watch the
+/// sudden death and how much they go after the other players.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    /// Dawdles now and then, and only bombs walls.
+    Easy,
+    Normal,
+    /// Goes for the other players before walls and power-ups.
+    Hard,
+}
+
+impl Default for Difficulty {
+    fn default() -> Self {
+        Difficulty::Normal
+    }
+}
+
+impl Difficulty {
+    /// How many of the upcoming sudden death walls a bot keeps away from.
+    fn wall_drop_lookahead(self) -> usize {
+        match self {
+            Difficulty::Easy => 1,
+            Difficulty::Normal => 4,
+            Difficulty::Hard => 8,
+        }
+    }
+
+    /// Ticks out of every second the bot stands still, when out of danger.
+    fn idle_ticks(self) -> u32 {
+        match self {
+            Difficulty::Easy => TICKS_PER_SECOND / 3,
+            Difficulty::Normal | Difficulty::Hard => 0,
+        }
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Difficulty::Easy => "easy",
+            Difficulty::Normal => "normal",
+            Difficulty::Hard => "hard",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Difficulty {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "easy" => Ok(Difficulty::Easy),
+            "normal" => Ok(Difficulty::Normal),
+            "hard" => Ok(Difficulty::Hard),
+            _ => Err("expected easy, normal or hard".to_string()),
+        }
+    }
+}
+
+/// Decides what the player `number` should do next: flee from blast lines,
+/// bomb walls and enemies when it can get away with it, or walk to the next
+/// power-up or spot worth bombing. Bots of any `difficulty` play the same way
+/// given the same state, so matches between them can be replayed from a seed.
+pub fn think(simulation: &Simulation, number: u8, difficulty: Difficulty) -> PlayerInput {
+    let map = &simulation.map;
+    let me = match simulation.player(number) {
+        Some(player) if player.alive => player,
+        _ => return PlayerInput::default(),
+    };
+    let (x, y) = (me.x, me.y);
+    let num_bombs = me.num_bombs;
+    let power = me.power;
+
+    let mut danger = grid(map, false);
+    let mut bomb_tiles = grid(map, false);
+    let mut flames = grid(map, false);
+    for bomb in &simulation.bombs {
+        bomb_tiles[bomb.coordinates[0]][bomb.coordinates[1]] = true;
+        mark_blast(map, &mut danger, bomb.coordinates, bomb.power);
+    }
+    // the next few walls of the sudden death are as deadly as a blast
+    for c in simulation
+        .next_wall_drops()
+        .take(difficulty.wall_drop_lookahead())
+    {
+        danger[c[0]][c[1]] = true;
+    }
+    for explosion in &simulation.explosions {
+        for flame in &explosion.flames {
+            flames[flame.coordinates[0]][flame.coordinates[1]] = true;
+        }
+    }
+    for (danger_column, flame_column) in danger.iter_mut().zip(&flames) {
+        for (danger, flame) in danger_column.iter_mut().zip(flame_column) {
+            *danger |= *flame;
+        }
+    }
+    let mut power_up_tiles = grid(map, false);
+    for power_up in &simulation.power_ups {
+        power_up_tiles[power_up.coordinates[0]][power_up.coordinates[1]] = true;
+    }
+    // easy bots leave the other players alone
+    let enemies: Vec<Coordinates> = simulation
+        .players
+        .iter()
+        .filter(|player| difficulty != Difficulty::Easy && player.alive && !player.is_ally(me))
+        .map(|player| map.get_tile(player.x, player.y).coordinates)
+        .collect();
+
+    let position = map.get_tile(x, y).coordinates;
+    let walkable = |c: Coordinates| {
+        map.get_tile_by_key(c[0], c[1]).status == TileStatus::Free
+            && (!bomb_tiles[c[0]][c[1]] || c == position)
+            && (!flames[c[0]][c[1]] || c == position)
+    };
+    let is_safe = |grid: &Grid<bool>, c: Coordinates| !grid[c[0]][c[1]];
+
+    // standing in a blast line, run to the closest tile no bomb can reach
+    if danger[position[0]][position[1]] {
+        let next = find_next_step(map, position, &walkable, |c| is_safe(&danger, c));
+        return steer((x, y), position, next);
+    }
+
+    // bots are told apart by their number, so they do not all dawdle at once
+    let idle_tick = (simulation.tick() + u32::from(number) * 7) % TICKS_PER_SECOND;
+    if idle_tick < difficulty.idle_ticks() {
+        return PlayerInput::default();
+    }
+
+    // out of harm's way, so it is fine to set off the bombs left behind once
+    // standing in the middle of the tile, where no flame can reach
+    let centered = is_centered((x, y), position);
+    let has_remote_bombs = simulation
+        .bombs
+        .iter()
+        .any(|bomb| bomb.remote && bomb.player_number == number);
+    if me.has_remote_detonator && has_remote_bombs && centered {
+        return PlayerInput {
+            detonate: true,
+            ..PlayerInput::default()
+        };
+    }
+
+    if num_bombs > 0 && centered && worth_bombing(map, position, &enemies, power) {
+        let mut danger_after_bomb = danger.clone();
+        mark_blast(map, &mut danger_after_bomb, position, power);
+        if find_next_step(map, position, &walkable, |c| is_safe(&danger_after_bomb, c)).is_some() {
+            return PlayerInput {
+                fire: true,
+                ..PlayerInput::default()
+            };
+        }
+    }
+
+    // hard bots first look for a spot to bomb someone from
+    let hunting = difficulty == Difficulty::Hard && num_bombs > 0;
+    let next = if hunting {
+        find_next_step(
+            map,
+            position,
+            |c| walkable(c) && is_safe(&danger, c),
+            |c| (c != position || !centered) && in_blast(map, c, &enemies, power),
+        )
+    } else {
+        None
+    };
+    if next.is_some() {
+        return steer((x, y), position, next);
+    }
+
+    // otherwise walk, through safe tiles only, to the closest power-up or spot
+    // worth bombing; the current tile is fine to wait on when out of bombs, or
+    // to walk to the middle of before bombing it
+    let next = find_next_step(
+        map,
+        position,
+        |c| walkable(c) && is_safe(&danger, c),
+        |c| {
+            (c != position && power_up_tiles[c[0]][c[1]])
+                || ((c != position || num_bombs == 0 || !centered)
+                    && worth_bombing(map, c, &enemies, power))
+        },
+    );
+    steer((x, y), position, next)
+}
+
+fn worth_bombing(map: &Map, origin: Coordinates, enemies: &[Coordinates], power: u8) -> bool {
+    let next_to_wall = neighbours(map, origin)
+        .into_iter()
+        .any(|c| map.get_tile_by_key(c[0], c[1]).status == TileStatus::Wall);
+    next_to_wall || in_blast(map, origin, enemies, power)
+}
+
+/// Whether a bomb at `origin` would reach one of the `enemies`.
+fn in_blast(map: &Map, origin: Coordinates, enemies: &[Coordinates], power: u8) -> bool {
+    let mut blast = grid(map, false);
+    mark_blast(map, &mut blast, origin, power);
+    enemies.iter().any(|c| blast[c[0]][c[1]])
+}
+
+/// Marks every tile the flames of a bomb at `origin` would reach.
+fn mark_blast(map: &Map, grid: &mut Grid<bool>, origin: Coordinates, power: u8) {
+    grid[origin[0]][origin[1]] = true;
+    for direction in &[(0, 1), (1, 0), (0, -1), (-1, 0)] {
+        for distance in 1..=i32::from(power) {
+            let x = origin[0] as i32 + direction.0 * distance;
+            let y = origin[1] as i32 + direction.1 * distance;
+            if x < 0 || x >= map.width() as i32 || y < 0 || y >= map.height() as i32 {
+                break;
+            }
+            if map.get_tile_by_key(x as usize, y as usize).status != TileStatus::Free {
+                break;
+            }
+            grid[x as usize][y as usize] = true;
+        }
+    }
+}
+
+/// Breadth-first search from `start` to the closest tile matching `goal`,
+/// returning the first tile to walk to in order to get there.
+fn find_next_step<W, G>(map: &Map, start: Coordinates, walkable: W, goal: G) -> Option<Coordinates>
+where
+    W: Fn(Coordinates) -> bool,
+    G: Fn(Coordinates) -> bool,
+{
+    let mut first_steps: Grid<Option<Coordinates>> = grid(map, None);
+    let mut visited = grid(map, false);
+    let mut queue = VecDeque::new();
+    visited[start[0]][start[1]] = true;
+    queue.push_back(start);
+    while let Some(current) = queue.pop_front() {
+        let first_step = first_steps[current[0]][current[1]];
+        if goal(current) {
+            return Some(first_step.unwrap_or(start));
+        }
+        for next in neighbours(map, current) {
+            if visited[next[0]][next[1]] || !walkable(next) {
+                continue;
+            }
+            visited[next[0]][next[1]] = true;
+            first_steps[next[0]][next[1]] = first_step.or(Some(next));
+            queue.push_back(next);
+        }
+    }
+    None
+}
+
+fn neighbours(map: &Map, c: Coordinates) -> Vec<Coordinates> {
+    let mut result = Vec::with_capacity(4);
+    if c[1] + 1 < map.height() {
+        result.push([c[0], c[1] + 1]);
+    }
+    if c[0] + 1 < map.width() {
+        result.push([c[0] + 1, c[1]]);
+    }
+    if c[1] > 0 {
+        result.push([c[0], c[1] - 1]);
+    }
+    if c[0] > 0 {
+        result.push([c[0] - 1, c[1]]);
+    }
+    result
+}
+
+fn tile_center(c: Coordinates) -> (f32, f32) {
+    (
+        c[0] as f32 * TILE_WIDTH + TILE_WIDTH_HALF,
+        c[1] as f32 * TILE_HEIGHT + TILE_HEIGHT_HALF,
+    )
+}
+
+fn is_centered((x, y): (f32, f32), position: Coordinates) -> bool {
+    let (center_x, center_y) = tile_center(position);
+    (center_x - x).abs() <= ALIGNMENT_TOLERANCE && (center_y - y).abs() <= ALIGNMENT_TOLERANCE
+}
+
+/// Turns the next tile of a path into axis values. Before changing tiles the
+/// bot lines itself up with the lane it is going to walk through.
+fn steer((x, y): (f32, f32), position: Coordinates, next: Option<Coordinates>) -> PlayerInput {
+    let mut input = PlayerInput::default();
+    let next = match next {
+        Some(next) => next,
+        None => return input,
+    };
+    let (center_x, center_y) = tile_center(position);
+    let offset_x = center_x - x;
+    let offset_y = center_y - y;
+    if next[0] != position[0] {
+        if offset_y.abs() > ALIGNMENT_TOLERANCE {
+            input.movement_y = offset_y.signum();
+        } else {
+            input.movement_x = if next[0] > position[0] { 1. } else { -1. };
+        }
+    } else if next[1] != position[1] {
+        if offset_x.abs() > ALIGNMENT_TOLERANCE {
+            input.movement_x = offset_x.signum();
+        } else {
+            input.movement_y = if next[1] > position[1] { 1. } else { -1. };
+        }
+    } else {
+        if offset_x.abs() > ALIGNMENT_TOLERANCE {
+            input.movement_x = offset_x.signum();
+        }
+        if offset_y.abs() > ALIGNMENT_TOLERANCE {
+            input.movement_y = offset_y.signum();
+        }
+    }
+    input
+}
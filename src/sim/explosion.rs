@@ -0,0 +1,132 @@
+use ncollide2d::bounding_volume::AABB;
+use ncollide2d::math::Point;
+
+use serde::{Deserialize, Serialize};
+
+use super::map::{Map, TileStatus, TILE_HEIGHT, TILE_WIDTH};
+
+/// Ticks a kicked bomb takes to slide over one tile.
+pub const KICK_TICKS: u32 = 3;
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Bomb {
+    pub id: u32,
+    pub coordinates: [usize; 2],
+    pub created_tick: u32,
+    pub power: u8,
+    pub player_number: u8,
+    /// Remote bombs ignore the fuse and wait for their owner to set them off.
+    pub remote: bool,
+    /// Direction a kicked bomb slides in, one tile per `KICK_TICKS`.
+    pub sliding: Option<(i32, i32)>,
+    pub moved_tick: u32,
+    /// Bombs block everyone, except the player who just placed it until they
+    /// have left its tile.
+    pub owner_on_top: bool,
+}
+
+/// The piece of flame drawn over a tile: the center of the explosion, a
+/// middle segment of an arm or the end of an arm, pointing away.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum FlamePiece {
+    Center,
+    Horizontal,
+    Vertical,
+    UpEnd,
+    RightEnd,
+    DownEnd,
+    LeftEnd,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Flame {
+    pub coordinates: [usize; 2],
+    pub piece: FlamePiece,
+}
+
+impl Flame {
+    pub fn bounding_box(&self) -> AABB<f32> {
+        tile_bbox(self.coordinates[0] as i32, self.coordinates[1] as i32)
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Explosion {
+    pub id: u32,
+    pub created_tick: u32,
+    /// Owner of the bomb that went off.
+    pub player_number: u8,
+    /// One flame per tile, the center first. Players burn on the very tiles
+    /// the flames are drawn on.
+    pub flames: Vec<Flame>,
+}
+
+impl Explosion {
+    pub fn collision_polygons(&self) -> impl Iterator<Item = AABB<f32>> + '_ {
+        self.flames.iter().map(Flame::bounding_box)
+    }
+
+    /// Whether the flames are over the tile at `coordinates`.
+    pub fn covers(&self, coordinates: [usize; 2]) -> bool {
+        self.flames
+            .iter()
+            .any(|flame| flame.coordinates == coordinates)
+    }
+}
+
+pub fn tile_bbox(x: i32, y: i32) -> AABB<f32> {
+    AABB::new(
+        Point::new(x as f32 * TILE_WIDTH, y as f32 * TILE_HEIGHT),
+        Point::new(
+            x as f32 * TILE_WIDTH + TILE_WIDTH,
+            y as f32 * TILE_HEIGHT + TILE_HEIGHT,
+        ),
+    )
+}
+
+/// Spreads the flames of `bomb` over the map. Each arm stops at the first
+/// wall on its way, which is destroyed and reported in `destroyed_walls`.
+/// Returns the flame of every tile reached, the center first.
+pub fn blast(map: &mut Map, bomb: &Bomb, destroyed_walls: &mut Vec<[usize; 2]>) -> Vec<Flame> {
+    let x = bomb.coordinates[0] as i32;
+    let y = bomb.coordinates[1] as i32;
+    let mut flames = vec![Flame {
+        coordinates: bomb.coordinates,
+        piece: FlamePiece::Center,
+    }];
+    let arms = [
+        ((0, 1), FlamePiece::Vertical, FlamePiece::UpEnd),
+        ((1, 0), FlamePiece::Horizontal, FlamePiece::RightEnd),
+        ((0, -1), FlamePiece::Vertical, FlamePiece::DownEnd),
+        ((-1, 0), FlamePiece::Horizontal, FlamePiece::LeftEnd),
+    ];
+    for ((dx, dy), middle, end) in arms.iter() {
+        let mut arm = Vec::new();
+        for j in 1..(bomb.power as i32 + 1) {
+            let (x, y) = (x + dx * j, y + dy * j);
+            if x < 0 || x >= map.width() as i32 || y < 0 || y >= map.height() as i32 {
+                break;
+            }
+            let coordinates = [x as usize, y as usize];
+            let next_tile = map.get_tile_by_key(coordinates[0], coordinates[1]);
+            match next_tile.status {
+                TileStatus::Wall => {
+                    map.update_tile(coordinates[0], coordinates[1], TileStatus::Free);
+                    destroyed_walls.push(coordinates);
+                    break;
+                }
+                TileStatus::Free => arm.push(Flame {
+                    coordinates,
+                    piece: *middle,
+                }),
+                TileStatus::PermanentWall => break,
+            }
+        }
+        // the last tile reached caps the arm
+        if let Some(last) = arm.last_mut() {
+            last.piece = *end;
+        }
+        flames.extend(arm);
+    }
+    flames
+}
@@ -0,0 +1,286 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use std::collections::VecDeque;
+
+use super::map::{Map, TileStatus};
+
+/// Tries at placing extra permanent walls before giving up on them, should they
+/// keep cutting players off from each other.
+const MAX_ATTEMPTS: u32 = 32;
+/// Generated maps have a spawn point in each corner.
+pub const SPAWN_POINTS: usize = 4;
+
+/// How the walls of a generated map repeat themselves, so that no player gets
+/// a better start than the others.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Symmetry {
+    None,
+    /// Mirrored left to right and top to bottom, every corner is the same.
+    Mirror,
+    /// Turned half a circle around the middle of the map.
+    Rotational,
+}
+
+impl Symmetry {
+    /// The tiles a tile at `[x, y]` stands for on a `width` x `height` map,
+    /// itself included.
+    fn orbit(self, width: usize, height: usize, [x, y]: [usize; 2]) -> Vec<[usize; 2]> {
+        let (right, top) = (width - 1, height - 1);
+        let mut tiles = match self {
+            Symmetry::None => vec![[x, y]],
+            Symmetry::Mirror => vec![[x, y], [right - x, y], [x, top - y], [right - x, top - y]],
+            Symmetry::Rotational => vec![[x, y], [right - x, top - y]],
+        };
+        tiles.sort();
+        tiles.dedup();
+        tiles
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GeneratorSettings {
+    /// Odd sizes only, so that the pillars line up the same way on both
+    /// sides. Even ones are rounded up.
+    pub width: usize,
+    pub height: usize,
+    /// Chance, from 0 to 1, of a tile getting a wall to destroy.
+    pub wall_density: f32,
+    /// Chance, from 0 to 1, of a tile getting a permanent wall on top of the
+    /// pillars.
+    pub block_density: f32,
+    pub symmetry: Symmetry,
+}
+
+impl Default for GeneratorSettings {
+    fn default() -> Self {
+        GeneratorSettings {
+            width: 13,
+            height: 11,
+            wall_density: 0.7,
+            block_density: 0.05,
+            symmetry: Symmetry::Mirror,
+        }
+    }
+}
+
+/// Draws a map with a player in each corner. Every player has room to get away
+/// from their first bomb, and can reach every other player once the walls in
+/// between are destroyed. The same `seed` and settings give the same map.
+pub fn generate(settings: &GeneratorSettings, seed: u64) -> Map {
+    let width = (settings.width.max(5)) | 1;
+    let height = (settings.height.max(5)) | 1;
+    let mut rng = StdRng::seed_from_u64(seed);
+
+    let mut map = Map::new(width, height);
+    map.name = format!("Generated {}", seed);
+    let (right, top) = (width - 1, height - 1);
+    map.spawn_points = vec![
+        (0, [0, 0]),
+        (1, [right, 0]),
+        (2, [0, top]),
+        (3, [right, top]),
+    ];
+    // each corner and the two tiles next to it are kept free to flee along
+    let escape_tiles: Vec<[usize; 2]> = map
+        .spawn_points
+        .iter()
+        .flat_map(|&(_, [x, y])| {
+            let next_x = if x == 0 { 1 } else { x - 1 };
+            let next_y = if y == 0 { 1 } else { y - 1 };
+            vec![[x, y], [next_x, y], [x, next_y]]
+        })
+        .collect();
+
+    // the classic pillars on every other tile
+    for x in (1..width).step_by(2) {
+        for y in (1..height).step_by(2) {
+            map.update_tile(x, y, TileStatus::PermanentWall);
+        }
+    }
+
+    let pillars = map.clone();
+    for attempt in 0..=MAX_ATTEMPTS {
+        map = pillars.clone();
+        // the last attempt goes without extra permanent walls, which cannot
+        // cut anyone off
+        let block_density = if attempt == MAX_ATTEMPTS {
+            0.
+        } else {
+            settings.block_density
+        };
+        place(
+            &mut map,
+            &mut rng,
+            settings.symmetry,
+            &escape_tiles,
+            |rng| {
+                if rng.gen::<f32>() < block_density {
+                    Some(TileStatus::PermanentWall)
+                } else if rng.gen::<f32>() < settings.wall_density {
+                    Some(TileStatus::Wall)
+                } else {
+                    None
+                }
+            },
+        );
+        if all_connected(&map) {
+            break;
+        }
+    }
+    map
+}
+
+/// Rolls what goes on every free tile left, once per group of symmetric
+/// tiles, leaving the `escape_tiles` alone.
+fn place<F>(
+    map: &mut Map,
+    rng: &mut StdRng,
+    symmetry: Symmetry,
+    escape_tiles: &[[usize; 2]],
+    mut roll: F,
+) where
+    F: FnMut(&mut StdRng) -> Option<TileStatus>,
+{
+    let (width, height) = (map.width(), map.height());
+    for x in 0..width {
+        for y in 0..height {
+            let orbit = symmetry.orbit(width, height, [x, y]);
+            // the first tile of the group rolls for all of them
+            if orbit[0] != [x, y] {
+                continue;
+            }
+            let status = roll(rng);
+            for &[x, y] in &orbit {
+                let free = map.get_tile_by_key(x, y).status == TileStatus::Free;
+                if let Some(status) = status {
+                    if free && !escape_tiles.contains(&[x, y]) {
+                        map.update_tile(x, y, status);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Whether every spawn point can reach the others through anything but
+/// permanent walls.
+fn all_connected(map: &Map) -> bool {
+    let start = match map.spawn_points.first() {
+        Some(&(_, start)) => start,
+        None => return true,
+    };
+    let (width, height) = (map.width(), map.height());
+    let mut reached = vec![vec![false; height]; width];
+    let mut queue = VecDeque::new();
+    reached[start[0]][start[1]] = true;
+    queue.push_back(start);
+    while let Some([x, y]) = queue.pop_front() {
+        let neighbours = [
+            (x as i32 - 1, y as i32),
+            (x as i32 + 1, y as i32),
+            (x as i32, y as i32 - 1),
+            (x as i32, y as i32 + 1),
+        ];
+        for &(x, y) in &neighbours {
+            if x < 0 || y < 0 || x >= width as i32 || y >= height as i32 {
+                continue;
+            }
+            let (x, y) = (x as usize, y as usize);
+            if reached[x][y] || map.get_tile_by_key(x, y).status == TileStatus::PermanentWall {
+                continue;
+            }
+            reached[x][y] = true;
+            queue.push_back([x, y]);
+        }
+    }
+    map.spawn_points.iter().all(|&(_, [x, y])| reached[x][y])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SYMMETRIES: [Symmetry; 3] = [Symmetry::None, Symmetry::Mirror, Symmetry::Rotational];
+
+    /// A few maps of every symmetry, along with the settings they come from.
+    fn maps() -> Vec<(GeneratorSettings, u64, Map)> {
+        let mut maps = Vec::new();
+        for &symmetry in &SYMMETRIES {
+            for &(width, height) in &[(13, 11), (9, 7), (5, 5)] {
+                for seed in 0..20 {
+                    let settings = GeneratorSettings {
+                        width,
+                        height,
+                        symmetry,
+                        ..GeneratorSettings::default()
+                    };
+                    let map = generate(&settings, seed);
+                    maps.push((settings, seed, map));
+                }
+            }
+        }
+        maps
+    }
+
+    #[test]
+    fn every_corner_has_room_to_escape() {
+        for (_, _, map) in maps() {
+            assert_eq!(map.spawn_points.len(), SPAWN_POINTS);
+            for &(_, [x, y]) in &map.spawn_points {
+                let next_x = if x == 0 { 1 } else { x - 1 };
+                let next_y = if y == 0 { 1 } else { y - 1 };
+                for &[x, y] in &[[x, y], [next_x, y], [x, next_y]] {
+                    assert_eq!(map.get_tile_by_key(x, y).status, TileStatus::Free);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_player_can_reach_the_others() {
+        for (_, _, map) in maps() {
+            assert!(all_connected(&map));
+        }
+    }
+
+    #[test]
+    fn walls_follow_the_symmetry() {
+        for (settings, _, map) in maps() {
+            let (width, height) = (map.width(), map.height());
+            for x in 0..width {
+                for y in 0..height {
+                    let status = map.get_tile_by_key(x, y).status;
+                    for [x, y] in settings.symmetry.orbit(width, height, [x, y]) {
+                        assert_eq!(map.get_tile_by_key(x, y).status, status);
+                    }
+                }
+            }
+        }
+        // the orbits themselves
+        assert_eq!(Symmetry::Mirror.orbit(5, 3, [1, 0]).len(), 4);
+        assert_eq!(
+            Symmetry::Rotational.orbit(5, 3, [1, 0]),
+            vec![[1, 0], [3, 2]]
+        );
+        assert_eq!(Symmetry::None.orbit(5, 3, [1, 0]), vec![[1, 0]]);
+    }
+
+    #[test]
+    fn sizes_are_odd_and_at_least_five() {
+        let settings = GeneratorSettings {
+            width: 12,
+            height: 2,
+            ..GeneratorSettings::default()
+        };
+        let map = generate(&settings, 0);
+        assert_eq!((map.width(), map.height()), (13, 5));
+    }
+
+    #[test]
+    fn the_same_seed_gives_the_same_map() {
+        for (settings, seed, map) in maps() {
+            assert_eq!(generate(&settings, seed), map);
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use serde::{Deserialize, Serialize};
+
+use super::power_up::{DropRates, PowerUpKind};
+
+pub const TILE_WIDTH: f32 = 16.0;
+pub const TILE_HEIGHT: f32 = 16.0;
+
+pub const TILE_WIDTH_HALF: f32 = TILE_WIDTH / 2.0;
+pub const TILE_HEIGHT_HALF: f32 = TILE_HEIGHT / 2.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TileStatus {
+    Free,
+    Wall,
+    PermanentWall,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Tile {
+    pub status: TileStatus,
+    pub coordinates: [usize; 2],
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Map {
+    pub name: String,
+    pub author: String,
+    /// Name of the track played on the map, the default one when empty.
+    pub music: String,
+    /// Where each player starts, by player number.
+    pub spawn_points: Vec<(u8, [usize; 2])>,
+    /// Power-ups lying on the floor when the round starts.
+    pub power_ups: Vec<([usize; 2], PowerUpKind)>,
+    /// What destroyed walls leave behind.
+    pub drop_rates: DropRates,
+    width: usize,
+    height: usize,
+    tiles: Vec<Tile>,
+}
+
+impl Map {
+    /// Creates a `width` x `height` map where every tile is free.
+    pub fn new(width: usize, height: usize) -> Self {
+        let tiles = (0..width)
+            .flat_map(|x| {
+                (0..height).map(move |y| Tile {
+                    status: TileStatus::Free,
+                    coordinates: [x, y],
+                })
+            })
+            .collect();
+        Map {
+            name: String::new(),
+            author: String::new(),
+            music: String::new(),
+            spawn_points: Vec::new(),
+            power_ups: Vec::new(),
+            drop_rates: DropRates::default(),
+            width,
+            height,
+            tiles,
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn arena_width(&self) -> f32 {
+        self.width as f32 * TILE_WIDTH
+    }
+
+    pub fn arena_height(&self) -> f32 {
+        self.height as f32 * TILE_HEIGHT
+    }
+
+    pub fn tiles(&self) -> impl Iterator<Item = &Tile> {
+        self.tiles.iter()
+    }
+
+    pub fn get_tile(&self, x: f32, y: f32) -> Tile {
+        let grid_x = (x / TILE_WIDTH).floor() as usize;
+        let grid_y = (y / TILE_HEIGHT).floor() as usize;
+        self.get_tile_by_key(grid_x, grid_y)
+    }
+
+    pub fn get_tile_by_key(&self, x: usize, y: usize) -> Tile {
+        self.tiles[x * self.height + y]
+    }
+
+    pub fn update_tile(&mut self, x: usize, y: usize, status: TileStatus) {
+        self.tiles[x * self.height + y].status = status;
+    }
+}
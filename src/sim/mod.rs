@@ -0,0 +1,1021 @@
+//! The rules of the game, free of any amethyst type so they can run, and be
+//! tested, without a window. The ECS systems feed it with inputs and mirror
+//! the events it produces on screen.
+
+use ncollide2d::bounding_volume::BoundingVolume;
+
+use log::info;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::time::Duration;
+
+pub use self::ai::Difficulty;
+pub use self::explosion::{Bomb, Explosion, Flame, FlamePiece};
+pub use self::map::{Map, TileStatus};
+pub use self::player::{Facing, Player, PlayerInput};
+pub use self::power_up::{PowerUp, PowerUpKind};
+pub use self::round::{Match, RoundOutcome, Side};
+pub use self::rules::GameRules;
+pub use self::snapshot::Snapshot;
+pub use self::stats::{DeathCause, Stats};
+pub use self::sudden_death::SuddenDeath;
+
+pub mod ai;
+pub mod explosion;
+pub mod generator;
+pub mod map;
+pub mod movement;
+pub mod player;
+pub mod power_up;
+pub mod round;
+pub mod rules;
+pub mod snapshot;
+pub mod stats;
+pub mod sudden_death;
+
+use self::explosion::{tile_bbox, KICK_TICKS};
+use self::map::{TILE_HEIGHT, TILE_HEIGHT_HALF, TILE_WIDTH, TILE_WIDTH_HALF};
+use self::player::{PLAYER_HEIGHT_HALF, PLAYER_WIDTH_HALF};
+use self::round::ROUND_END_TICKS;
+
+/// The simulation only ever advances by a whole tick, whatever the frame rate,
+/// so the same inputs always play out the same way.
+pub const TICKS_PER_SECOND: u32 = 60;
+/// Game time covered by a tick.
+pub const TICK: Duration = Duration::from_nanos(1_000_000_000 / TICKS_PER_SECOND as u64);
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameEvent {
+    BombPlaced {
+        id: u32,
+        player_number: u8,
+        coordinates: [usize; 2],
+    },
+    BombMoved {
+        id: u32,
+        coordinates: [usize; 2],
+    },
+    BombExploded {
+        id: u32,
+    },
+    /// A bomb was crushed by a falling wall without going off.
+    BombCrushed {
+        id: u32,
+    },
+    ExplosionCreated {
+        id: u32,
+        flames: Vec<Flame>,
+    },
+    ExplosionFaded {
+        id: u32,
+    },
+    WallDestroyed {
+        coordinates: [usize; 2],
+    },
+    WallDropped {
+        coordinates: [usize; 2],
+    },
+    PowerUpDropped {
+        id: u32,
+        kind: PowerUpKind,
+        coordinates: [usize; 2],
+    },
+    PowerUpCollected {
+        id: u32,
+        player_number: u8,
+        kind: PowerUpKind,
+    },
+    PowerUpDestroyed {
+        id: u32,
+    },
+    PlayerDied {
+        number: u8,
+        cause: DeathCause,
+    },
+}
+
+pub struct Simulation {
+    /// Counts the rounds played since the game was launched.
+    pub round: u32,
+    pub map: Map,
+    pub players: Vec<Player>,
+    pub bombs: Vec<Bomb>,
+    pub explosions: Vec<Explosion>,
+    pub power_ups: Vec<PowerUp>,
+    /// The round is a draw when nobody wins before this tick.
+    pub tick_limit: u32,
+    pub sudden_death: Option<SuddenDeath>,
+    /// See `movement::move_player`, 0 turns the corner sliding off.
+    pub corner_tolerance: f32,
+    /// Whether the flames of a player hurt their teammates.
+    pub friendly_fire: bool,
+    /// What every player did this round.
+    pub stats: Stats,
+    /// What the round is played with. The round time, the sudden death and
+    /// the corner tolerance are copied into their own fields at the start.
+    pub rules: GameRules,
+    tick: u32,
+    /// When the round was left with at most one player standing.
+    last_stand_tick: Option<u32>,
+    /// Tiles left for the walls of the sudden death to fall on, in order.
+    wall_drops: VecDeque<[usize; 2]>,
+    last_wall_drop_tick: Option<u32>,
+    next_id: u32,
+    rng: StdRng,
+}
+
+impl Simulation {
+    /// Starts a game on `map` with one player on each of its spawn points,
+    /// played with `rules`. Two games with the same `seed` and rules fed the
+    /// same inputs end the same way.
+    pub fn new(map: Map, seed: u64, rules: GameRules) -> Self {
+        let players = map
+            .spawn_points
+            .iter()
+            .map(|&(number, coordinates)| {
+                Player::new(
+                    number,
+                    coordinates[0] as f32 * TILE_WIDTH + TILE_WIDTH_HALF,
+                    coordinates[1] as f32 * TILE_HEIGHT + TILE_HEIGHT_HALF,
+                    &rules,
+                )
+            })
+            .collect();
+
+        let wall_drops = sudden_death::spiral(map.width(), map.height()).into();
+        let mut simulation = Simulation {
+            round: 0,
+            map,
+            players,
+            bombs: Vec::new(),
+            explosions: Vec::new(),
+            power_ups: Vec::new(),
+            tick_limit: rules.round_ticks(),
+            sudden_death: rules.sudden_death_time.map(|seconds| SuddenDeath {
+                remaining_ticks: seconds * TICKS_PER_SECOND,
+                interval: rules.wall_drop_ticks(),
+            }),
+            corner_tolerance: rules.corner_tolerance,
+            friendly_fire: true,
+            stats: Stats::default(),
+            rules,
+            tick: 0,
+            last_stand_tick: None,
+            wall_drops,
+            last_wall_drop_tick: None,
+            next_id: 0,
+            rng: StdRng::seed_from_u64(seed),
+        };
+        for (coordinates, kind) in simulation.map.power_ups.clone() {
+            let id = simulation.next_id();
+            simulation.power_ups.push(PowerUp {
+                id,
+                coordinates,
+                kind,
+            });
+        }
+        simulation
+    }
+
+    /// Ticks played since the start of the round.
+    pub fn tick(&self) -> u32 {
+        self.tick
+    }
+
+    /// Ticks left before the round is a draw.
+    pub fn remaining_ticks(&self) -> u32 {
+        self.tick_limit.saturating_sub(self.tick)
+    }
+
+    pub fn player(&self, number: u8) -> Option<&Player> {
+        self.players.iter().find(|player| player.number == number)
+    }
+
+    /// Splits the players into `teams` teams, taking turns by number, or
+    /// back into a free-for-all below two teams.
+    pub fn assign_teams(&mut self, teams: u8) {
+        for player in &mut self.players {
+            player.team = if teams > 1 {
+                Some(player.number % teams)
+            } else {
+                None
+            };
+        }
+    }
+
+    /// How the round ended, once a single side has outlived the others for
+    /// `ROUND_END_TICKS`, everyone is dead or the tick limit is reached, which
+    /// is a draw.
+    pub fn outcome(&self) -> Option<RoundOutcome> {
+        let over = match self.last_stand_tick {
+            Some(tick) => self.tick - tick >= ROUND_END_TICKS,
+            None => self.tick >= self.tick_limit,
+        };
+        if !over {
+            return None;
+        }
+        match (self.surviving_sides().as_slice(), self.last_stand_tick) {
+            ([side], Some(_)) => Some(RoundOutcome::Winner(*side)),
+            _ => Some(RoundOutcome::Draw),
+        }
+    }
+
+    /// The sides with at least one player alive, in the order of the players.
+    fn surviving_sides(&self) -> Vec<Side> {
+        let mut sides: Vec<Side> = Vec::new();
+        for player in self.players.iter().filter(|player| player.alive) {
+            if !sides.contains(&player.side()) {
+                sides.push(player.side());
+            }
+        }
+        sides
+    }
+
+    /// The tiles the next walls of the sudden death will fall on, soonest
+    /// first, once it has started.
+    pub fn next_wall_drops(&self) -> impl Iterator<Item = &[usize; 2]> {
+        let upcoming = if self.sudden_death_started() {
+            self.wall_drops.len()
+        } else {
+            0
+        };
+        self.wall_drops.iter().take(upcoming).filter(move |c| {
+            self.map.get_tile_by_key(c[0], c[1]).status != TileStatus::PermanentWall
+        })
+    }
+
+    pub fn sudden_death_started(&self) -> bool {
+        match &self.sudden_death {
+            Some(sudden_death) => self.tick + sudden_death.remaining_ticks >= self.tick_limit,
+            None => false,
+        }
+    }
+
+    /// Advances the game by one tick, applying the inputs of every player found
+    /// in `inputs`, and returns what happened along the way.
+    pub fn step(&mut self, inputs: &HashMap<u8, PlayerInput>) -> Vec<GameEvent> {
+        self.tick += 1;
+        let mut events = Vec::new();
+        self.move_players(inputs);
+        self.kick_bombs(inputs);
+        self.slide_bombs(&mut events);
+        self.collect_power_ups(&mut events);
+        self.place_bombs(inputs, &mut events);
+        self.drop_walls(&mut events);
+        self.burn_players(&mut events);
+        self.check_last_stand();
+        self.fade_explosions(&mut events);
+        self.detonate_bombs(inputs, &mut events);
+        self.burn_power_ups(&mut events);
+        for player in self.players.iter().filter(|player| player.alive) {
+            self.stats.player_mut(player.number).ticks_alive += 1;
+        }
+        events
+    }
+
+    fn next_id(&mut self) -> u32 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    fn move_players(&mut self, inputs: &HashMap<u8, PlayerInput>) {
+        let map = &self.map;
+        let corner_tolerance = self.corner_tolerance;
+        let bombs = &mut self.bombs;
+        for player in self.players.iter_mut().filter(|player| player.alive) {
+            let obstacles: Vec<[usize; 2]> = bombs
+                .iter()
+                .filter(|bomb| !(bomb.owner_on_top && bomb.player_number == player.number))
+                .map(|bomb| bomb.coordinates)
+                .collect();
+            match inputs.get(&player.number) {
+                Some(input) => {
+                    movement::move_player(map, &obstacles, corner_tolerance, player, input)
+                }
+                None => player.walking = false,
+            }
+            let bbox = player.bounding_box();
+            for bomb in bombs
+                .iter_mut()
+                .filter(|bomb| bomb.owner_on_top && bomb.player_number == player.number)
+            {
+                let [x, y] = bomb.coordinates;
+                bomb.owner_on_top = tile_bbox(x as i32, y as i32).intersects(&bbox);
+            }
+        }
+    }
+
+    /// Sets bombs right in front of players who can kick sliding, away from them.
+    fn kick_bombs(&mut self, inputs: &HashMap<u8, PlayerInput>) {
+        for player in self.players.iter().filter(|p| p.alive && p.can_kick) {
+            let input = match inputs.get(&player.number) {
+                Some(input) => input,
+                None => continue,
+            };
+            let direction = if input.movement_x != 0. {
+                (input.movement_x.signum() as i32, 0)
+            } else if input.movement_y != 0. {
+                (0, input.movement_y.signum() as i32)
+            } else {
+                continue;
+            };
+            let front_x = player.x + direction.0 as f32 * (PLAYER_WIDTH_HALF + 1.);
+            let front_y = player.y + direction.1 as f32 * (PLAYER_HEIGHT_HALF + 1.);
+            if front_x < 0.
+                || front_x >= self.map.arena_width()
+                || front_y < 0.
+                || front_y >= self.map.arena_height()
+            {
+                continue;
+            }
+            let front = self.map.get_tile(front_x, front_y).coordinates;
+            if front == self.map.get_tile(player.x, player.y).coordinates {
+                continue;
+            }
+            if let Some(bomb) = self
+                .bombs
+                .iter_mut()
+                .find(|bomb| bomb.coordinates == front && bomb.sliding.is_none())
+            {
+                bomb.sliding = Some(direction);
+                bomb.moved_tick = self.tick;
+            }
+        }
+    }
+
+    /// Moves kicked bombs until they run into a wall, a bomb or a player.
+    fn slide_bombs(&mut self, events: &mut Vec<GameEvent>) {
+        for i in 0..self.bombs.len() {
+            let direction = match self.bombs[i].sliding {
+                Some(direction) => direction,
+                None => continue,
+            };
+            if self.tick - self.bombs[i].moved_tick < KICK_TICKS {
+                continue;
+            }
+            let x = self.bombs[i].coordinates[0] as i32 + direction.0;
+            let y = self.bombs[i].coordinates[1] as i32 + direction.1;
+            let next = [x as usize, y as usize];
+            let blocked = x < 0
+                || x >= self.map.width() as i32
+                || y < 0
+                || y >= self.map.height() as i32
+                || self.map.get_tile_by_key(next[0], next[1]).status != TileStatus::Free
+                || self.bombs.iter().any(|bomb| bomb.coordinates == next)
+                || self.players.iter().any(|player| {
+                    player.alive && player.bounding_box().intersects(&tile_bbox(x, y))
+                });
+            let bomb = &mut self.bombs[i];
+            if blocked {
+                bomb.sliding = None;
+                continue;
+            }
+            bomb.coordinates = next;
+            bomb.moved_tick = self.tick;
+            events.push(GameEvent::BombMoved {
+                id: bomb.id,
+                coordinates: next,
+            });
+        }
+    }
+
+    fn collect_power_ups(&mut self, events: &mut Vec<GameEvent>) {
+        let power_ups = &mut self.power_ups;
+        let rules = &self.rules;
+        for player in self.players.iter_mut().filter(|player| player.alive) {
+            let bbox = player.bounding_box();
+            while let Some(index) = power_ups.iter().position(|power_up| {
+                let [x, y] = power_up.coordinates;
+                tile_bbox(x as i32, y as i32).intersects(&bbox)
+            }) {
+                let power_up = power_ups.remove(index);
+                player.collect(power_up.kind, rules);
+                self.stats.player_mut(player.number).power_ups_collected += 1;
+                info!("player {} collected {:?}", player.number, power_up.kind);
+                events.push(GameEvent::PowerUpCollected {
+                    id: power_up.id,
+                    player_number: player.number,
+                    kind: power_up.kind,
+                });
+            }
+        }
+    }
+
+    fn place_bombs(&mut self, inputs: &HashMap<u8, PlayerInput>, events: &mut Vec<GameEvent>) {
+        for i in 0..self.players.len() {
+            let player = &self.players[i];
+            let fire = inputs.get(&player.number).map_or(false, |input| input.fire);
+            if !player.alive || !fire || player.num_bombs == 0 {
+                continue;
+            }
+            let coordinates = self.map.get_tile(player.x, player.y).coordinates;
+            // a single bomb fits on a tile
+            if self
+                .bombs
+                .iter()
+                .any(|bomb| bomb.coordinates == coordinates)
+            {
+                continue;
+            }
+            let player_number = player.number;
+            let power = player.power;
+            let remote = player.has_remote_detonator;
+            let id = self.next_id();
+            let player = &mut self.players[i];
+            player.num_bombs -= 1;
+            info!("spawning, {}", player.num_bombs);
+            self.stats.player_mut(player_number).bombs_placed += 1;
+            self.bombs.push(Bomb {
+                id,
+                coordinates,
+                created_tick: self.tick,
+                power,
+                player_number,
+                remote,
+                sliding: None,
+                moved_tick: self.tick,
+                owner_on_top: true,
+            });
+            events.push(GameEvent::BombPlaced {
+                id,
+                player_number,
+                coordinates,
+            });
+        }
+    }
+
+    fn drop_walls(&mut self, events: &mut Vec<GameEvent>) {
+        let interval = match &self.sudden_death {
+            Some(sudden_death) if self.sudden_death_started() => sudden_death.interval,
+            _ => return,
+        };
+        if let Some(tick) = self.last_wall_drop_tick {
+            if self.tick - tick < interval {
+                return;
+            }
+        }
+        // tiles which already are permanent walls are skipped right away
+        while let Some(coordinates) = self.wall_drops.pop_front() {
+            let [x, y] = coordinates;
+            if self.map.get_tile_by_key(x, y).status == TileStatus::PermanentWall {
+                continue;
+            }
+            self.last_wall_drop_tick = Some(self.tick);
+            self.map.update_tile(x, y, TileStatus::PermanentWall);
+            events.push(GameEvent::WallDropped { coordinates });
+
+            let tile = tile_bbox(x as i32, y as i32);
+            for player in self.players.iter_mut().filter(|player| player.alive) {
+                let bbox = player.bounding_box();
+                let under_wall = bbox.mins().x < tile.maxs().x
+                    && tile.mins().x < bbox.maxs().x
+                    && bbox.mins().y < tile.maxs().y
+                    && tile.mins().y < bbox.maxs().y;
+                if under_wall {
+                    player.alive = false;
+                    info!("player {} crushed", player.number);
+                    let cause = DeathCause::Crushed;
+                    self.stats
+                        .record_death(self.round, self.tick, player.number, cause);
+                    events.push(GameEvent::PlayerDied {
+                        number: player.number,
+                        cause,
+                    });
+                }
+            }
+            let (crushed, bombs): (Vec<Bomb>, Vec<Bomb>) = self
+                .bombs
+                .drain(..)
+                .partition(|bomb| bomb.coordinates == coordinates);
+            self.bombs = bombs;
+            for bomb in crushed {
+                if let Some(owner) = self
+                    .players
+                    .iter_mut()
+                    .find(|player| player.number == bomb.player_number)
+                {
+                    owner.num_bombs = (owner.num_bombs + 1).min(owner.max_bombs);
+                }
+                events.push(GameEvent::BombCrushed { id: bomb.id });
+            }
+            let (crushed, power_ups): (Vec<PowerUp>, Vec<PowerUp>) = self
+                .power_ups
+                .drain(..)
+                .partition(|power_up| power_up.coordinates == coordinates);
+            self.power_ups = power_ups;
+            for power_up in crushed {
+                events.push(GameEvent::PowerUpDestroyed { id: power_up.id });
+            }
+            break;
+        }
+    }
+
+    fn burn_players(&mut self, events: &mut Vec<GameEvent>) {
+        let explosions = &self.explosions;
+        let friendly_fire = self.friendly_fire;
+        let sides: HashMap<u8, Side> = self
+            .players
+            .iter()
+            .map(|player| (player.number, player.side()))
+            .collect();
+        for player in self.players.iter_mut().filter(|player| player.alive) {
+            let bbox = player.bounding_box();
+            // the oldest flames get the kill when several overlap
+            let burning = explosions.iter().find(|explosion| {
+                // players still burn in their own flames without friendly fire
+                let hurts = friendly_fire
+                    || explosion.player_number == player.number
+                    || sides.get(&explosion.player_number) != Some(&player.side());
+                hurts
+                    && explosion
+                        .collision_polygons()
+                        .any(|polygon| polygon.intersects(&bbox))
+            });
+            if let Some(explosion) = burning {
+                player.alive = false;
+                info!(
+                    "player {} killed by the bomb of player {}",
+                    player.number, explosion.player_number
+                );
+                let cause = DeathCause::Bomb(explosion.player_number);
+                self.stats
+                    .record_death(self.round, self.tick, player.number, cause);
+                events.push(GameEvent::PlayerDied {
+                    number: player.number,
+                    cause,
+                });
+            }
+        }
+    }
+
+    fn check_last_stand(&mut self) {
+        if self.last_stand_tick.is_some() {
+            return;
+        }
+        // a lone player, or a lone team, keeps playing until they blow
+        // themselves up
+        let sides = self
+            .players
+            .iter()
+            .map(Player::side)
+            .collect::<HashSet<_>>()
+            .len();
+        let last_standing = if sides > 1 { 1 } else { 0 };
+        if self.surviving_sides().len() <= last_standing {
+            self.last_stand_tick = Some(self.tick);
+        }
+    }
+
+    fn fade_explosions(&mut self, events: &mut Vec<GameEvent>) {
+        let tick = self.tick;
+        let flame_ticks = self.rules.flame_ticks();
+        let (faded, burning): (Vec<Explosion>, Vec<Explosion>) = self
+            .explosions
+            .drain(..)
+            .partition(|explosion| tick - explosion.created_tick >= flame_ticks);
+        self.explosions = burning;
+        for explosion in faded {
+            events.push(GameEvent::ExplosionFaded { id: explosion.id });
+        }
+    }
+
+    fn detonate_bombs(&mut self, inputs: &HashMap<u8, PlayerInput>, events: &mut Vec<GameEvent>) {
+        let tick = self.tick;
+        let fuse_ticks = self.rules.fuse_ticks();
+        // remote bombs of dead players go off right away instead of staying forever
+        let detonating: Vec<u8> = self
+            .players
+            .iter()
+            .filter(|player| {
+                !player.alive
+                    || inputs
+                        .get(&player.number)
+                        .map_or(false, |input| input.detonate)
+            })
+            .map(|player| player.number)
+            .collect();
+        let (due, ticking): (Vec<Bomb>, Vec<Bomb>) = self.bombs.drain(..).partition(|bomb| {
+            if bomb.remote {
+                detonating.contains(&bomb.player_number)
+            } else {
+                tick - bomb.created_tick >= fuse_ticks
+            }
+        });
+        self.bombs = ticking;
+
+        // bombs caught in flames go off on the spot, possibly setting off more
+        // bombs in turn; they are resolved first come first served, and in the
+        // order they were placed when caught by the same blast
+        let mut queue: VecDeque<Bomb> = due.into_iter().collect();
+        loop {
+            let explosions = &self.explosions;
+            let (caught, ticking): (Vec<Bomb>, Vec<Bomb>) =
+                self.bombs.drain(..).partition(|bomb| {
+                    explosions
+                        .iter()
+                        .any(|explosion| explosion.covers(bomb.coordinates))
+                });
+            self.bombs = ticking;
+            queue.extend(caught);
+            match queue.pop_front() {
+                Some(bomb) => self.detonate(bomb, events),
+                None => break,
+            }
+        }
+    }
+
+    fn burn_power_ups(&mut self, events: &mut Vec<GameEvent>) {
+        let explosions = &self.explosions;
+        let (burnt, intact): (Vec<PowerUp>, Vec<PowerUp>) =
+            self.power_ups.drain(..).partition(|power_up| {
+                explosions
+                    .iter()
+                    .any(|explosion| explosion.covers(power_up.coordinates))
+            });
+        self.power_ups = intact;
+        for power_up in burnt {
+            events.push(GameEvent::PowerUpDestroyed { id: power_up.id });
+        }
+    }
+
+    fn detonate(&mut self, bomb: Bomb, events: &mut Vec<GameEvent>) {
+        events.push(GameEvent::BombExploded { id: bomb.id });
+        let mut destroyed_walls = Vec::new();
+        let flames = explosion::blast(&mut self.map, &bomb, &mut destroyed_walls);
+        self.stats.player_mut(bomb.player_number).walls_destroyed += destroyed_walls.len() as u32;
+        for coordinates in destroyed_walls {
+            events.push(GameEvent::WallDestroyed { coordinates });
+            if let Some(kind) = self.map.drop_rates.roll(&mut self.rng) {
+                let id = self.next_id();
+                self.power_ups.push(PowerUp {
+                    id,
+                    coordinates,
+                    kind,
+                });
+                events.push(GameEvent::PowerUpDropped {
+                    id,
+                    kind,
+                    coordinates,
+                });
+            }
+        }
+        let id = self.next_id();
+        events.push(GameEvent::ExplosionCreated {
+            id,
+            flames: flames.clone(),
+        });
+        self.explosions.push(Explosion {
+            id,
+            created_tick: self.tick,
+            player_number: bomb.player_number,
+            flames,
+        });
+        if let Some(player) = self
+            .players
+            .iter_mut()
+            .find(|player| player.number == bomb.player_number)
+        {
+            player.num_bombs = (player.num_bombs + 1).min(player.max_bombs);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bomb(id: u32, coordinates: [usize; 2], power: u8, player_number: u8) -> Bomb {
+        Bomb {
+            id,
+            coordinates,
+            created_tick: 0,
+            power,
+            player_number,
+            remote: false,
+            sliding: None,
+            moved_tick: 0,
+            owner_on_top: false,
+        }
+    }
+
+    /// A map one tile high with a player on each of `spawn_points`.
+    fn corridor(width: usize, spawn_points: &[[usize; 2]]) -> Map {
+        let mut map = Map::new(width, 1);
+        map.spawn_points = spawn_points
+            .iter()
+            .enumerate()
+            .map(|(number, &coordinates)| (number as u8, coordinates))
+            .collect();
+        map
+    }
+
+    /// Steps with the same `inputs` for `ticks` ticks, returning every event.
+    fn run(
+        simulation: &mut Simulation,
+        inputs: &HashMap<u8, PlayerInput>,
+        ticks: u32,
+    ) -> Vec<GameEvent> {
+        (0..ticks).flat_map(|_| simulation.step(inputs)).collect()
+    }
+
+    fn fuse_ticks() -> u32 {
+        GameRules::default().fuse_ticks()
+    }
+
+    #[test]
+    fn blast_stops_at_and_destroys_the_first_wall() {
+        let mut map = corridor(7, &[]);
+        map.update_tile(0, 0, TileStatus::PermanentWall);
+        map.update_tile(3, 0, TileStatus::Wall);
+        map.update_tile(4, 0, TileStatus::Wall);
+        let mut simulation = Simulation::new(map, 0, GameRules::default());
+        simulation.bombs.push(bomb(100, [1, 0], 3, 0));
+
+        let events = run(&mut simulation, &HashMap::new(), fuse_ticks());
+
+        assert!(events.contains(&GameEvent::BombExploded { id: 100 }));
+        assert!(events.contains(&GameEvent::WallDestroyed {
+            coordinates: [3, 0]
+        }));
+        let map = &simulation.map;
+        assert_eq!(map.get_tile_by_key(0, 0).status, TileStatus::PermanentWall);
+        assert_eq!(map.get_tile_by_key(3, 0).status, TileStatus::Free);
+        assert_eq!(map.get_tile_by_key(4, 0).status, TileStatus::Wall);
+        let explosion = &simulation.explosions[0];
+        assert!(explosion.covers([1, 0]) && explosion.covers([2, 0]));
+        assert!(!explosion.covers([0, 0]));
+        assert!(!explosion.covers([3, 0]) && !explosion.covers([4, 0]));
+    }
+
+    #[test]
+    fn bombs_caught_in_a_blast_go_off_with_it() {
+        let mut simulation = Simulation::new(corridor(7, &[]), 0, GameRules::default());
+        simulation.bombs.push(bomb(100, [1, 0], 2, 0));
+        let mut events = run(&mut simulation, &HashMap::new(), fuse_ticks() / 2);
+        // far from going off on its own
+        let mut second = bomb(101, [3, 0], 2, 1);
+        second.created_tick = simulation.tick();
+        simulation.bombs.push(second);
+
+        events.extend(run(&mut simulation, &HashMap::new(), fuse_ticks() / 2));
+
+        assert!(events.contains(&GameEvent::BombExploded { id: 100 }));
+        assert!(events.contains(&GameEvent::BombExploded { id: 101 }));
+        assert!(simulation.bombs.is_empty());
+        assert_eq!(simulation.explosions.len(), 2);
+        assert!(simulation
+            .explosions
+            .iter()
+            .any(|explosion| explosion.covers([5, 0])));
+    }
+
+    #[test]
+    fn players_in_the_blast_die() {
+        let map = corridor(5, &[[3, 0], [0, 0]]);
+        let mut simulation = Simulation::new(map, 0, GameRules::default());
+        simulation.bombs.push(bomb(100, [2, 0], 1, 1));
+
+        // the flames burn from the tick after the bomb goes off
+        let events = run(&mut simulation, &HashMap::new(), fuse_ticks() + 1);
+
+        assert!(events.contains(&GameEvent::PlayerDied {
+            number: 0,
+            cause: DeathCause::Bomb(1),
+        }));
+        assert!(!simulation.player(0).unwrap().alive);
+        assert!(simulation.player(1).unwrap().alive);
+    }
+
+    #[test]
+    fn players_get_their_bomb_back_once_it_went_off() {
+        let mut simulation = Simulation::new(corridor(3, &[[0, 0]]), 0, GameRules::default());
+        let mut fire = HashMap::new();
+        fire.insert(
+            0,
+            PlayerInput {
+                fire: true,
+                ..PlayerInput::default()
+            },
+        );
+
+        let events = simulation.step(&fire);
+        assert_eq!(
+            events,
+            vec![GameEvent::BombPlaced {
+                id: 0,
+                player_number: 0,
+                coordinates: [0, 0],
+            }]
+        );
+        assert_eq!(simulation.player(0).unwrap().num_bombs, 0);
+        assert!(simulation.step(&fire).is_empty());
+
+        run(&mut simulation, &HashMap::new(), fuse_ticks());
+        assert!(simulation.bombs.is_empty());
+        assert_eq!(simulation.player(0).unwrap().num_bombs, 1);
+    }
+
+    #[test]
+    fn bombs_block_everyone_but_their_owner_standing_on_them() {
+        let map = corridor(5, &[[1, 0], [3, 0]]);
+        let mut simulation = Simulation::new(map, 0, GameRules::default());
+        let walk = |number: u8, movement_x: f32| {
+            let mut inputs = HashMap::new();
+            inputs.insert(
+                number,
+                PlayerInput {
+                    movement_x,
+                    ..PlayerInput::default()
+                },
+            );
+            inputs
+        };
+        let mut fire = HashMap::new();
+        fire.insert(
+            0,
+            PlayerInput {
+                fire: true,
+                ..PlayerInput::default()
+            },
+        );
+        simulation.step(&fire);
+        assert_eq!(simulation.bombs[0].coordinates, [1, 0]);
+
+        // the owner walks about on their bomb
+        let x = simulation.player(0).unwrap().x;
+        simulation.step(&walk(0, 1.));
+        assert!(simulation.player(0).unwrap().x > x);
+
+        // but cannot get back on it once off
+        run(&mut simulation, &walk(0, -1.), 20);
+        assert!(!simulation.bombs[0].owner_on_top);
+        run(&mut simulation, &walk(0, 1.), 20);
+        assert!(simulation.player(0).unwrap().x + PLAYER_WIDTH_HALF <= TILE_WIDTH);
+
+        // nor can anyone else
+        run(&mut simulation, &walk(1, -1.), 20);
+        assert!(simulation.player(1).unwrap().x - PLAYER_WIDTH_HALF >= 2. * TILE_WIDTH);
+        assert_eq!(simulation.bombs.len(), 1);
+    }
+
+    #[test]
+    fn remote_bombs_placed_and_set_off_in_the_same_step_explode_after_placing() {
+        let map = corridor(5, &[[0, 0], [4, 0]]);
+        let mut simulation = Simulation::new(map, 0, GameRules::default());
+        simulation.players[0].has_remote_detonator = true;
+        let mut inputs = HashMap::new();
+        inputs.insert(
+            0,
+            PlayerInput {
+                fire: true,
+                detonate: true,
+                ..PlayerInput::default()
+            },
+        );
+
+        let events = simulation.step(&inputs);
+
+        let placed = events
+            .iter()
+            .position(|event| match event {
+                GameEvent::BombPlaced { .. } => true,
+                _ => false,
+            })
+            .expect("no bomb placed");
+        let id = match &events[placed] {
+            GameEvent::BombPlaced { id, .. } => *id,
+            _ => unreachable!(),
+        };
+        let exploded = events
+            .iter()
+            .position(|event| *event == GameEvent::BombExploded { id })
+            .expect("the bomb did not explode");
+        assert!(placed < exploded);
+        assert!(events[exploded..].iter().any(|event| match event {
+            GameEvent::ExplosionCreated { .. } => true,
+            _ => false,
+        }));
+        assert!(simulation.bombs.is_empty());
+    }
+
+    #[test]
+    fn the_same_seed_and_inputs_play_out_the_same() {
+        let map = generator::generate(&generator::GeneratorSettings::default(), 7);
+        let mut simulations = vec![
+            Simulation::new(map.clone(), 42, GameRules::default()),
+            Simulation::new(map, 42, GameRules::default()),
+        ];
+        let mut events = vec![Vec::new(), Vec::new()];
+        for _ in 0..3000 {
+            // the bots of the first simulation play both
+            let inputs: HashMap<u8, PlayerInput> = simulations[0]
+                .players
+                .iter()
+                .map(|player| {
+                    let input = ai::think(&simulations[0], player.number, Difficulty::Hard);
+                    (player.number, input)
+                })
+                .collect();
+            for (simulation, events) in simulations.iter_mut().zip(&mut events) {
+                events.extend(simulation.step(&inputs));
+            }
+            assert_eq!(simulations[0].outcome(), simulations[1].outcome());
+            if simulations[0].outcome().is_some() {
+                break;
+            }
+        }
+        assert!(events[0].iter().any(|event| match event {
+            GameEvent::WallDestroyed { .. } => true,
+            _ => false,
+        }));
+        assert_eq!(events[0], events[1]);
+        assert_eq!(simulations[0].map, simulations[1].map);
+    }
+
+    #[test]
+    fn the_last_survivor_wins_once_they_outlived_the_others() {
+        let map = corridor(5, &[[3, 0], [0, 0]]);
+        let mut simulation = Simulation::new(map, 0, GameRules::default());
+        simulation.bombs.push(bomb(100, [2, 0], 1, 1));
+
+        run(&mut simulation, &HashMap::new(), fuse_ticks() + 1);
+        assert!(!simulation.player(0).unwrap().alive);
+        run(&mut simulation, &HashMap::new(), ROUND_END_TICKS - 1);
+        assert_eq!(simulation.outcome(), None);
+        simulation.step(&HashMap::new());
+        assert_eq!(
+            simulation.outcome(),
+            Some(RoundOutcome::Winner(Side::Player(1)))
+        );
+    }
+
+    #[test]
+    fn players_dying_together_draw() {
+        let map = corridor(5, &[[1, 0], [3, 0]]);
+        let mut simulation = Simulation::new(map, 0, GameRules::default());
+        simulation.bombs.push(bomb(100, [2, 0], 1, 1));
+
+        run(&mut simulation, &HashMap::new(), fuse_ticks() + 1);
+        assert!(simulation.players.iter().all(|player| !player.alive));
+        run(&mut simulation, &HashMap::new(), ROUND_END_TICKS);
+        assert_eq!(simulation.outcome(), Some(RoundOutcome::Draw));
+    }
+
+    #[test]
+    fn rounds_running_out_of_time_draw() {
+        let map = corridor(5, &[[0, 0], [4, 0]]);
+        let mut simulation = Simulation::new(map, 0, GameRules::default());
+        simulation.sudden_death = None;
+        simulation.tick_limit = 10;
+
+        run(&mut simulation, &HashMap::new(), 9);
+        assert_eq!(simulation.outcome(), None);
+        simulation.step(&HashMap::new());
+        assert!(simulation.players.iter().all(|player| player.alive));
+        assert_eq!(simulation.outcome(), Some(RoundOutcome::Draw));
+    }
+
+    #[test]
+    fn falling_walls_crush_the_players_beneath() {
+        let map = corridor(5, &[[0, 0], [4, 0]]);
+        let mut simulation = Simulation::new(map, 0, GameRules::default());
+        // the walls start falling right away
+        simulation.tick_limit = 100;
+
+        let events = simulation.step(&HashMap::new());
+
+        assert!(events.contains(&GameEvent::WallDropped {
+            coordinates: [0, 0]
+        }));
+        assert!(events.contains(&GameEvent::PlayerDied {
+            number: 0,
+            cause: DeathCause::Crushed,
+        }));
+        assert_eq!(
+            simulation.map.get_tile_by_key(0, 0).status,
+            TileStatus::PermanentWall
+        );
+        assert!(simulation.player(1).unwrap().alive);
+
+        // the next wall waits for the interval
+        let interval = simulation.rules.wall_drop_ticks();
+        let drops = |events: &[GameEvent]| {
+            events
+                .iter()
+                .filter(|event| match event {
+                    GameEvent::WallDropped { .. } => true,
+                    _ => false,
+                })
+                .count()
+        };
+        assert_eq!(
+            drops(&run(&mut simulation, &HashMap::new(), interval - 1)),
+            0
+        );
+        assert_eq!(drops(&simulation.step(&HashMap::new())), 1);
+    }
+}
@@ -0,0 +1,204 @@
+use super::map::{
+    Map, Tile, TileStatus, TILE_HEIGHT, TILE_HEIGHT_HALF, TILE_WIDTH, TILE_WIDTH_HALF,
+};
+use super::player::{Facing, Player, PlayerInput, PLAYER_HEIGHT_HALF, PLAYER_WIDTH_HALF};
+
+/// How far, in pixels, the middle of a player may be from the middle of an
+/// open lane and still be slid into it when running into the wall next to it.
+pub const CORNER_TOLERANCE: f32 = 6.0;
+
+fn clamp_to_arena_vertical_boundaries(map: &Map, value: f32) -> f32 {
+    value
+        .min(map.arena_height() - PLAYER_HEIGHT_HALF)
+        .max(PLAYER_HEIGHT_HALF)
+}
+
+fn clamp_to_arena_horizontal_boundaries(map: &Map, value: f32) -> f32 {
+    value
+        .min(map.arena_width() - PLAYER_WIDTH_HALF)
+        .max(PLAYER_WIDTH_HALF)
+}
+
+/// Whether the player can step on `tile`: it has to be free and not taken by
+/// one of the `obstacles`, such as bombs.
+fn is_walkable(tile: &Tile, obstacles: &[[usize; 2]]) -> bool {
+    tile.status == TileStatus::Free && !obstacles.contains(&tile.coordinates)
+}
+
+/// Moves `player` as far as `input` takes it without stepping over walls or
+/// the tiles of `obstacles`. A player running into a corner at most
+/// `corner_tolerance` pixels away from the middle of an open lane slides
+/// towards it instead of being stopped.
+pub fn move_player(
+    map: &Map,
+    obstacles: &[[usize; 2]],
+    corner_tolerance: f32,
+    player: &mut Player,
+    input: &PlayerInput,
+) {
+    let (x, y) = (player.x, player.y);
+    if input.movement_x != 0. {
+        move_horizontally(map, obstacles, corner_tolerance, player, input.movement_x);
+    }
+    if input.movement_y != 0. {
+        move_vertically(map, obstacles, corner_tolerance, player, input.movement_y);
+    }
+    let (dx, dy) = (player.x - x, player.y - y);
+    player.walking = dx != 0. || dy != 0.;
+    if player.walking {
+        // the larger move wins when going along both axes
+        player.facing = if dx.abs() >= dy.abs() {
+            if dx < 0. {
+                Facing::Left
+            } else {
+                Facing::Right
+            }
+        } else if dy < 0. {
+            Facing::Down
+        } else {
+            Facing::Up
+        };
+    }
+}
+
+/// Moves `position` towards `target` by at most `amount`.
+fn approach(position: f32, target: f32, amount: f32) -> f32 {
+    if (target - position).abs() <= amount {
+        target
+    } else {
+        position + amount.copysign(target - position)
+    }
+}
+
+fn move_horizontally(
+    map: &Map,
+    obstacles: &[[usize; 2]],
+    corner_tolerance: f32,
+    player: &mut Player,
+    mv_amount: f32,
+) {
+    let scaled_amount = player.speed * mv_amount;
+    let edge = if scaled_amount > 0.0 {
+        player.x + scaled_amount + PLAYER_WIDTH_HALF
+    } else {
+        player.x + scaled_amount - PLAYER_WIDTH_HALF
+    };
+    let x = clamp_to_arena_horizontal_boundaries(map, edge);
+    let target_tile_top = map.get_tile(
+        x,
+        clamp_to_arena_vertical_boundaries(map, player.y + PLAYER_HEIGHT_HALF),
+    );
+    let target_tile_bottom = map.get_tile(
+        x,
+        clamp_to_arena_vertical_boundaries(map, player.y - PLAYER_HEIGHT_HALF),
+    );
+    let top_walkable = is_walkable(&target_tile_top, obstacles);
+    let bottom_walkable = is_walkable(&target_tile_bottom, obstacles);
+    if top_walkable && bottom_walkable {
+        player.x = clamp_to_arena_horizontal_boundaries(map, player.x + scaled_amount);
+        return;
+    }
+    // only one of the two tiles ahead is open: slide into its lane when close
+    // enough to it
+    let open_tile = match (top_walkable, bottom_walkable) {
+        (true, false) => target_tile_top,
+        (false, true) => target_tile_bottom,
+        _ => return,
+    };
+    let lane_center = open_tile.coordinates[1] as f32 * TILE_HEIGHT + TILE_HEIGHT_HALF;
+    if (lane_center - player.y).abs() <= corner_tolerance {
+        player.y = approach(player.y, lane_center, scaled_amount.abs());
+    }
+}
+
+fn move_vertically(
+    map: &Map,
+    obstacles: &[[usize; 2]],
+    corner_tolerance: f32,
+    player: &mut Player,
+    mv_amount: f32,
+) {
+    let scaled_amount = player.speed * mv_amount;
+    let edge = if scaled_amount > 0.0 {
+        player.y + scaled_amount + PLAYER_HEIGHT_HALF
+    } else {
+        player.y + scaled_amount - PLAYER_HEIGHT_HALF
+    };
+    let y = clamp_to_arena_vertical_boundaries(map, edge);
+    let target_tile_right = map.get_tile(
+        clamp_to_arena_horizontal_boundaries(map, player.x + PLAYER_WIDTH_HALF),
+        y,
+    );
+    let target_tile_left = map.get_tile(
+        clamp_to_arena_horizontal_boundaries(map, player.x - PLAYER_WIDTH_HALF),
+        y,
+    );
+    let right_walkable = is_walkable(&target_tile_right, obstacles);
+    let left_walkable = is_walkable(&target_tile_left, obstacles);
+    if right_walkable && left_walkable {
+        player.y = clamp_to_arena_vertical_boundaries(map, player.y + scaled_amount);
+        return;
+    }
+    // only one of the two tiles ahead is open: slide into its lane when close
+    // enough to it
+    let open_tile = match (right_walkable, left_walkable) {
+        (true, false) => target_tile_right,
+        (false, true) => target_tile_left,
+        _ => return,
+    };
+    let lane_center = open_tile.coordinates[0] as f32 * TILE_WIDTH + TILE_WIDTH_HALF;
+    if (lane_center - player.x).abs() <= corner_tolerance {
+        player.x = approach(player.x, lane_center, scaled_amount.abs());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::rules::GameRules;
+
+    const RIGHT: PlayerInput = PlayerInput {
+        movement_x: 1.,
+        movement_y: 0.,
+        fire: false,
+        detonate: false,
+    };
+
+    /// A 3 x 3 map with a pillar in the middle, a player at `x`, `y` walking
+    /// right into it.
+    fn walk_into_pillar(x: f32, y: f32, corner_tolerance: f32, steps: u32) -> Player {
+        let mut map = Map::new(3, 3);
+        map.update_tile(1, 1, TileStatus::PermanentWall);
+        let mut player = Player::new(0, x, y, &GameRules::default());
+        for _ in 0..steps {
+            move_player(&map, &[], corner_tolerance, &mut player, &RIGHT);
+        }
+        player
+    }
+
+    #[test]
+    fn players_slide_around_corners_within_the_tolerance() {
+        // 4 pixels above the middle of the bottom lane
+        let player = walk_into_pillar(10., 12., CORNER_TOLERANCE, 1);
+        assert_eq!(player.x, 10.);
+        assert!(player.y < 12.);
+
+        // and on past the pillar once clear of it
+        let player = walk_into_pillar(10., 12., CORNER_TOLERANCE, 10);
+        assert!(player.y + PLAYER_HEIGHT_HALF < TILE_HEIGHT);
+        assert!(player.x > TILE_WIDTH);
+    }
+
+    #[test]
+    fn players_just_outside_the_tolerance_are_blocked() {
+        let player = walk_into_pillar(10., 15., CORNER_TOLERANCE, 10);
+        assert_eq!((player.x, player.y), (10., 15.));
+        assert!(!player.walking);
+    }
+
+    #[test]
+    fn no_tolerance_turns_sliding_off() {
+        let player = walk_into_pillar(10., 12., 0., 10);
+        assert_eq!((player.x, player.y), (10., 12.));
+    }
+}
@@ -0,0 +1,129 @@
+use ncollide2d::bounding_volume::AABB;
+use ncollide2d::math::Point;
+
+use serde::{Deserialize, Serialize};
+
+use super::power_up::PowerUpKind;
+use super::round::Side;
+use super::rules::GameRules;
+
+pub const PLAYER_WIDTH: f32 = 12.0;
+pub const PLAYER_HEIGHT: f32 = 12.0;
+
+pub const PLAYER_WIDTH_HALF: f32 = PLAYER_WIDTH / 2.0;
+pub const PLAYER_HEIGHT_HALF: f32 = PLAYER_HEIGHT / 2.0;
+
+/// Where a player looks, the way they last walked.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Facing {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Player {
+    pub number: u8,
+    /// Players of the same team win together, none in a free-for-all.
+    pub team: Option<u8>,
+    pub x: f32,
+    pub y: f32,
+    /// Bombs the player can still place; given back when they explode.
+    pub num_bombs: u8,
+    /// Bombs the player can have on the map at once.
+    pub max_bombs: u8,
+    /// How many tiles the flames of the player's bombs reach.
+    pub power: u8,
+    pub speed: f32,
+    pub can_kick: bool,
+    pub has_remote_detonator: bool,
+    pub alive: bool,
+    pub facing: Facing,
+    /// Whether the player moved during the last step.
+    pub walking: bool,
+}
+
+impl Player {
+    /// A player at `x`, `y` with what `rules` give to start with.
+    pub fn new(number: u8, x: f32, y: f32, rules: &GameRules) -> Self {
+        Player {
+            number,
+            team: None,
+            x,
+            y,
+            num_bombs: rules.starting_bombs,
+            max_bombs: rules.starting_bombs,
+            power: rules.starting_power,
+            speed: rules.walk_speed,
+            can_kick: false,
+            has_remote_detonator: false,
+            alive: true,
+            facing: Facing::Down,
+            walking: false,
+        }
+    }
+
+    pub fn side(&self) -> Side {
+        match self.team {
+            Some(team) => Side::Team(team),
+            None => Side::Player(self.number),
+        }
+    }
+
+    /// Whether `other` is the player themselves or one of their teammates.
+    pub fn is_ally(&self, other: &Player) -> bool {
+        self.side() == other.side()
+    }
+
+    /// Speed-ups collected plus one, the way the speed is shown to players.
+    pub fn speed_level(&self, rules: &GameRules) -> u8 {
+        if rules.speed_step <= 0. {
+            return 1;
+        }
+        ((self.speed - rules.walk_speed) / rules.speed_step)
+            .round()
+            .max(0.) as u8
+            + 1
+    }
+
+    pub fn bounding_box(&self) -> AABB<f32> {
+        AABB::new(
+            Point::new(self.x - PLAYER_WIDTH_HALF, self.y - PLAYER_HEIGHT_HALF),
+            Point::new(self.x + PLAYER_WIDTH_HALF, self.y + PLAYER_HEIGHT_HALF),
+        )
+    }
+
+    /// Gets better at what `kind` is about, within the limits of `rules`.
+    pub fn collect(&mut self, kind: PowerUpKind, rules: &GameRules) {
+        match kind {
+            PowerUpKind::BombUp => {
+                if self.max_bombs < rules.max_bombs {
+                    self.max_bombs += 1;
+                    self.num_bombs += 1;
+                }
+            }
+            PowerUpKind::FireUp => {
+                self.power = self.power.max((self.power + 1).min(rules.max_power))
+            }
+            PowerUpKind::SpeedUp => {
+                self.speed = self
+                    .speed
+                    .max((self.speed + rules.speed_step).min(rules.max_speed))
+            }
+            PowerUpKind::Kick => self.can_kick = true,
+            PowerUpKind::RemoteDetonator => self.has_remote_detonator = true,
+        }
+    }
+}
+
+/// What a player wants to do this step, regardless of whether it comes from
+/// the keyboard or from the AI.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct PlayerInput {
+    pub movement_x: f32,
+    pub movement_y: f32,
+    pub fire: bool,
+    /// Sets off the player's bombs when they have a remote detonator.
+    pub detonate: bool,
+}
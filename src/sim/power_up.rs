@@ -0,0 +1,66 @@
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PowerUpKind {
+    BombUp,
+    FireUp,
+    SpeedUp,
+    Kick,
+    RemoteDetonator,
+}
+
+/// A power-up lying on the floor, waiting for a player to walk over it.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PowerUp {
+    pub id: u32,
+    pub coordinates: [usize; 2],
+    pub kind: PowerUpKind,
+}
+
+/// Chance of a destroyed wall dropping each kind of power-up, between 0 and 1.
+/// They add up to the chance of dropping anything at all.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct DropRates {
+    pub bomb_up: f32,
+    pub fire_up: f32,
+    pub speed_up: f32,
+    pub kick: f32,
+    pub remote_detonator: f32,
+}
+
+impl Default for DropRates {
+    fn default() -> Self {
+        DropRates {
+            bomb_up: 0.1,
+            fire_up: 0.1,
+            speed_up: 0.05,
+            kick: 0.03,
+            remote_detonator: 0.02,
+        }
+    }
+}
+
+impl DropRates {
+    pub fn total(&self) -> f32 {
+        self.bomb_up + self.fire_up + self.speed_up + self.kick + self.remote_detonator
+    }
+
+    /// Picks what a destroyed wall leaves behind, if anything.
+    pub fn roll<R: Rng>(&self, rng: &mut R) -> Option<PowerUpKind> {
+        let mut roll: f32 = rng.gen();
+        for &(rate, kind) in &[
+            (self.bomb_up, PowerUpKind::BombUp),
+            (self.fire_up, PowerUpKind::FireUp),
+            (self.speed_up, PowerUpKind::SpeedUp),
+            (self.kick, PowerUpKind::Kick),
+            (self.remote_detonator, PowerUpKind::RemoteDetonator),
+        ] {
+            if roll < rate {
+                return Some(kind);
+            }
+            roll -= rate;
+        }
+        None
+    }
+}
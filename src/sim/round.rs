@@ -0,0 +1,111 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use super::TICKS_PER_SECOND;
+
+/// Ticks the last survivor has to stay alive for the round to be theirs.
+pub const ROUND_END_TICKS: u32 = 3 * TICKS_PER_SECOND / 2;
+
+/// Who rounds are won by: a player on their own, or a whole team.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Side {
+    Player(u8),
+    Team(u8),
+}
+
+impl Side {
+    /// Short name for the scores, such as `P1` or `T2`.
+    pub fn short_name(self) -> String {
+        match self {
+            Side::Player(number) => format!("P{}", number + 1),
+            Side::Team(team) => format!("T{}", team + 1),
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Side::Player(number) => write!(f, "Player {}", number + 1),
+            Side::Team(team) => write!(f, "Team {}", team + 1),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RoundOutcome {
+    Winner(Side),
+    Draw,
+}
+
+/// Round wins of every side over a match.
+#[derive(Clone, Debug)]
+pub struct Match {
+    rounds_to_win: u8,
+    rounds_played: u32,
+    wins: BTreeMap<Side, u8>,
+}
+
+impl Match {
+    /// The first side to win `rounds_to_win` rounds takes the match.
+    pub fn new(rounds_to_win: u8) -> Self {
+        Match {
+            rounds_to_win,
+            rounds_played: 0,
+            wins: BTreeMap::new(),
+        }
+    }
+
+    pub fn record(&mut self, outcome: RoundOutcome) {
+        self.rounds_played += 1;
+        if let RoundOutcome::Winner(side) = outcome {
+            *self.wins.entry(side).or_insert(0) += 1;
+        }
+    }
+
+    pub fn rounds_played(&self) -> u32 {
+        self.rounds_played
+    }
+
+    pub fn wins(&self, side: Side) -> u8 {
+        self.wins.get(&side).cloned().unwrap_or(0)
+    }
+
+    pub fn winner(&self) -> Option<Side> {
+        self.wins
+            .iter()
+            .find(|(_, wins)| **wins >= self.rounds_to_win)
+            .map(|(side, _)| *side)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_first_side_to_win_enough_rounds_takes_the_match() {
+        let mut game_match = Match::new(2);
+        game_match.record(RoundOutcome::Winner(Side::Player(0)));
+        game_match.record(RoundOutcome::Draw);
+        game_match.record(RoundOutcome::Winner(Side::Player(1)));
+        assert_eq!(game_match.winner(), None);
+
+        game_match.record(RoundOutcome::Winner(Side::Player(1)));
+        assert_eq!(game_match.winner(), Some(Side::Player(1)));
+        assert_eq!(game_match.rounds_played(), 4);
+        assert_eq!(game_match.wins(Side::Player(0)), 1);
+        assert_eq!(game_match.wins(Side::Player(1)), 2);
+        assert_eq!(game_match.wins(Side::Player(2)), 0);
+    }
+
+    #[test]
+    fn draws_win_nobody_anything() {
+        let mut game_match = Match::new(1);
+        for _ in 0..3 {
+            game_match.record(RoundOutcome::Draw);
+        }
+        assert_eq!(game_match.winner(), None);
+        assert_eq!(game_match.rounds_played(), 3);
+    }
+}
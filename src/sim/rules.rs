@@ -0,0 +1,76 @@
+use serde::{Deserialize, Serialize};
+
+use super::movement::CORNER_TOLERANCE;
+use super::TICKS_PER_SECOND;
+
+/// What a game is played with, read from `config/rules/<preset>.ron`. Times
+/// are in seconds and turned into ticks by the simulation. Missing fields keep
+/// their classic value.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameRules {
+    /// From placing a bomb to its explosion.
+    pub fuse_time: f32,
+    /// How long the flames of an explosion last.
+    pub flame_time: f32,
+    /// Pixels a player walks per tick at first.
+    pub walk_speed: f32,
+    /// Speed gained with each speed-up.
+    pub speed_step: f32,
+    pub max_speed: f32,
+    pub starting_bombs: u8,
+    pub max_bombs: u8,
+    /// How many tiles the flames of the first bombs reach.
+    pub starting_power: u8,
+    pub max_power: u8,
+    /// Before a round is a draw.
+    pub round_time: u32,
+    /// Time left in the round when the walls start falling, none without.
+    pub sudden_death_time: Option<u32>,
+    /// Between two walls falling.
+    pub wall_drop_interval: f32,
+    /// See `movement::move_player`, 0 turns the corner sliding off.
+    pub corner_tolerance: f32,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules {
+            fuse_time: 3.0,
+            flame_time: 0.5,
+            walk_speed: 1.2,
+            speed_step: 0.3,
+            max_speed: 2.4,
+            starting_bombs: 1,
+            max_bombs: 8,
+            starting_power: 1,
+            max_power: 8,
+            round_time: 180,
+            sudden_death_time: Some(60),
+            wall_drop_interval: 0.4,
+            corner_tolerance: CORNER_TOLERANCE,
+        }
+    }
+}
+
+impl GameRules {
+    pub fn fuse_ticks(&self) -> u32 {
+        seconds_to_ticks(self.fuse_time)
+    }
+
+    pub fn flame_ticks(&self) -> u32 {
+        seconds_to_ticks(self.flame_time)
+    }
+
+    pub fn wall_drop_ticks(&self) -> u32 {
+        seconds_to_ticks(self.wall_drop_interval)
+    }
+
+    pub fn round_ticks(&self) -> u32 {
+        self.round_time * TICKS_PER_SECOND
+    }
+}
+
+fn seconds_to_ticks(seconds: f32) -> u32 {
+    (seconds.max(0.) * TICKS_PER_SECOND as f32).round() as u32
+}
@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    Bomb, DeathCause, Explosion, GameEvent, GameRules, Map, Player, PowerUp, Simulation, Stats,
+    TileStatus,
+};
+
+/// The state of a `Simulation` at some point, enough for another one to
+/// mirror it without stepping on its own.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub round: u32,
+    pub tick: u32,
+    pub tick_limit: u32,
+    pub last_stand_tick: Option<u32>,
+    pub map: Map,
+    pub players: Vec<Player>,
+    pub bombs: Vec<Bomb>,
+    pub explosions: Vec<Explosion>,
+    pub power_ups: Vec<PowerUp>,
+    pub stats: Stats,
+    pub rules: GameRules,
+}
+
+impl Simulation {
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            round: self.round,
+            tick: self.tick,
+            tick_limit: self.tick_limit,
+            last_stand_tick: self.last_stand_tick,
+            map: self.map.clone(),
+            players: self.players.clone(),
+            bombs: self.bombs.clone(),
+            explosions: self.explosions.clone(),
+            power_ups: self.power_ups.clone(),
+            stats: self.stats.clone(),
+            rules: self.rules.clone(),
+        }
+    }
+
+    /// Catches up with `snapshot`, returning the events that would have led
+    /// from the current state to it.
+    pub fn apply_snapshot(&mut self, snapshot: Snapshot) -> Vec<GameEvent> {
+        let mut events = Vec::new();
+
+        for bomb in &self.bombs {
+            if !snapshot.bombs.iter().any(|other| other.id == bomb.id) {
+                events.push(GameEvent::BombExploded { id: bomb.id });
+            }
+        }
+        for tile in snapshot.map.tiles() {
+            let [x, y] = tile.coordinates;
+            let previous = self.map.get_tile_by_key(x, y).status;
+            let coordinates = tile.coordinates;
+            match (previous, tile.status) {
+                (TileStatus::Wall, TileStatus::Free) => {
+                    events.push(GameEvent::WallDestroyed { coordinates })
+                }
+                (TileStatus::Free, TileStatus::PermanentWall)
+                | (TileStatus::Wall, TileStatus::PermanentWall) => {
+                    events.push(GameEvent::WallDropped { coordinates })
+                }
+                _ => {}
+            }
+        }
+        for explosion in &self.explosions {
+            if !snapshot
+                .explosions
+                .iter()
+                .any(|other| other.id == explosion.id)
+            {
+                events.push(GameEvent::ExplosionFaded { id: explosion.id });
+            }
+        }
+        for explosion in &snapshot.explosions {
+            if !self.explosions.iter().any(|other| other.id == explosion.id) {
+                events.push(GameEvent::ExplosionCreated {
+                    id: explosion.id,
+                    flames: explosion.flames.clone(),
+                });
+            }
+        }
+        for power_up in &self.power_ups {
+            if !snapshot
+                .power_ups
+                .iter()
+                .any(|other| other.id == power_up.id)
+            {
+                events.push(GameEvent::PowerUpDestroyed { id: power_up.id });
+            }
+        }
+        for power_up in &snapshot.power_ups {
+            if !self.power_ups.iter().any(|other| other.id == power_up.id) {
+                events.push(GameEvent::PowerUpDropped {
+                    id: power_up.id,
+                    kind: power_up.kind,
+                    coordinates: power_up.coordinates,
+                });
+            }
+        }
+        for bomb in &snapshot.bombs {
+            match self.bombs.iter().find(|other| other.id == bomb.id) {
+                None => events.push(GameEvent::BombPlaced {
+                    id: bomb.id,
+                    player_number: bomb.player_number,
+                    coordinates: bomb.coordinates,
+                }),
+                Some(other) if other.coordinates != bomb.coordinates => {
+                    events.push(GameEvent::BombMoved {
+                        id: bomb.id,
+                        coordinates: bomb.coordinates,
+                    })
+                }
+                Some(_) => {}
+            }
+        }
+        for player in &snapshot.players {
+            let was_alive = self
+                .player(player.number)
+                .map_or(false, |other| other.alive);
+            if was_alive && !player.alive {
+                let cause = snapshot
+                    .stats
+                    .death(player.number)
+                    .map_or(DeathCause::Crushed, |death| death.cause);
+                events.push(GameEvent::PlayerDied {
+                    number: player.number,
+                    cause,
+                });
+            }
+        }
+
+        self.round = snapshot.round;
+        self.tick = snapshot.tick;
+        self.tick_limit = snapshot.tick_limit;
+        self.last_stand_tick = snapshot.last_stand_tick;
+        self.map = snapshot.map;
+        self.players = snapshot.players;
+        self.bombs = snapshot.bombs;
+        self.explosions = snapshot.explosions;
+        self.power_ups = snapshot.power_ups;
+        self.stats = snapshot.stats;
+        self.rules = snapshot.rules;
+        events
+    }
+}
@@ -0,0 +1,93 @@
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeMap;
+
+/// What killed a player.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum DeathCause {
+    /// Caught in the flames of a bomb of that player, who may be the one
+    /// who died.
+    Bomb(u8),
+    /// Crushed by a falling wall of the sudden death.
+    Crushed,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Death {
+    pub round: u32,
+    pub player_number: u8,
+    pub cause: DeathCause,
+    /// Tick of the round the player died on.
+    pub tick: u32,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct PlayerStats {
+    /// Other players caught in the flames of the player's bombs, teammates
+    /// included.
+    pub kills: u32,
+    /// Times the player was caught in their own flames.
+    pub suicides: u32,
+    pub deaths: u32,
+    pub bombs_placed: u32,
+    pub walls_destroyed: u32,
+    pub power_ups_collected: u32,
+    pub ticks_alive: u32,
+}
+
+impl PlayerStats {
+    fn add(&mut self, other: &PlayerStats) {
+        self.kills += other.kills;
+        self.suicides += other.suicides;
+        self.deaths += other.deaths;
+        self.bombs_placed += other.bombs_placed;
+        self.walls_destroyed += other.walls_destroyed;
+        self.power_ups_collected += other.power_ups_collected;
+        self.ticks_alive += other.ticks_alive;
+    }
+}
+
+/// What every player did over a round, or over several once added up.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub players: BTreeMap<u8, PlayerStats>,
+    /// Every death, in the order they happened.
+    pub deaths: Vec<Death>,
+}
+
+impl Stats {
+    pub fn player_mut(&mut self, number: u8) -> &mut PlayerStats {
+        self.players.entry(number).or_default()
+    }
+
+    pub fn record_death(&mut self, round: u32, tick: u32, number: u8, cause: DeathCause) {
+        self.player_mut(number).deaths += 1;
+        match cause {
+            DeathCause::Bomb(owner) if owner == number => self.player_mut(number).suicides += 1,
+            DeathCause::Bomb(owner) => self.player_mut(owner).kills += 1,
+            DeathCause::Crushed => {}
+        }
+        self.deaths.push(Death {
+            round,
+            player_number: number,
+            cause,
+            tick,
+        });
+    }
+
+    /// The last death of the player `number`, if any.
+    pub fn death(&self, number: u8) -> Option<&Death> {
+        self.deaths
+            .iter()
+            .rev()
+            .find(|death| death.player_number == number)
+    }
+
+    /// Adds up the stats of another round.
+    pub fn add(&mut self, other: &Stats) {
+        for (number, stats) in &other.players {
+            self.player_mut(*number).add(stats);
+        }
+        self.deaths.extend(other.deaths.iter().cloned());
+    }
+}
@@ -0,0 +1,66 @@
+/// Once the clock runs low, permanent walls start falling on the arena in a
+/// spiral, from the border inward, crushing whatever lies beneath. Both are
+/// taken from the `GameRules`.
+#[derive(Clone, Debug)]
+pub struct SuddenDeath {
+    /// Ticks left in the round when the first wall falls.
+    pub remaining_ticks: u32,
+    /// Ticks between two falling walls.
+    pub interval: u32,
+}
+
+/// Every tile of a `width` x `height` map, ring by ring from the border to the
+/// middle, clockwise from the bottom left corner.
+pub fn spiral(width: usize, height: usize) -> Vec<[usize; 2]> {
+    let mut tiles = Vec::with_capacity(width * height);
+    let (mut left, mut bottom) = (0, 0);
+    let (mut right, mut top) = (width, height);
+    while left < right && bottom < top {
+        for y in bottom..top {
+            tiles.push([left, y]);
+        }
+        for x in left + 1..right {
+            tiles.push([x, top - 1]);
+        }
+        if right - left > 1 {
+            for y in (bottom..top - 1).rev() {
+                tiles.push([right - 1, y]);
+            }
+        }
+        if top - bottom > 1 {
+            for x in (left + 1..right - 1).rev() {
+                tiles.push([x, bottom]);
+            }
+        }
+        left += 1;
+        bottom += 1;
+        right -= 1;
+        top -= 1;
+    }
+    tiles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_spiral_goes_over_every_tile_once() {
+        for &(width, height) in &[(13, 11), (5, 5), (6, 3), (1, 4), (4, 1), (1, 1)] {
+            let mut tiles = spiral(width, height);
+            assert_eq!(tiles[0], [0, 0]);
+            assert_eq!(tiles.len(), width * height);
+            tiles.sort();
+            tiles.dedup();
+            assert_eq!(tiles.len(), width * height);
+            assert!(tiles.iter().all(|&[x, y]| x < width && y < height));
+        }
+    }
+
+    #[test]
+    fn the_spiral_goes_from_the_border_inward() {
+        let ring = |[x, y]: [usize; 2]| x.min(y).min(12 - x).min(10 - y);
+        let rings: Vec<usize> = spiral(13, 11).into_iter().map(ring).collect();
+        assert!(rings.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+}
@@ -1,63 +1,51 @@
 use amethyst::{
     assets::{AssetStorage, Loader},
-    core::timing::Stopwatch,
     core::transform::Transform,
-    ecs::prelude::{Component, DenseVecStorage},
+    ecs::Entity,
     input::{get_key, is_close_requested, is_key_down, VirtualKeyCode},
     prelude::*,
     renderer::{
         sprite::SpriteSheetHandle, Camera, ImageFormat, SpriteRender, SpriteSheet,
         SpriteSheetFormat, Texture,
     },
+    ui::{get_default_font, Anchor, FontAsset, UiImage, UiText, UiTransform},
     window::ScreenDimensions,
 };
 
-use log::info;
+use log::{error, info, warn};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 
 use std::collections::HashMap;
+use std::fs;
 use std::time::Duration;
 
+use crate::audio::{self, AudioSettings, Music};
 use crate::config::read_map;
-use crate::entities::player;
-
-pub const ARENA_WIDTH: f32 = 208.0;
-pub const ARENA_HEIGHT: f32 = 176.0;
-
-pub const TILE_COUNT_VERTICAL: f32 = 11.0;
-pub const TILE_COUNT_HORIZONTAL: f32 = 13.0;
-
-pub const TILE_WIDTH: f32 = 16.0;
-pub const TILE_HEIGHT: f32 = 16.0;
-
-pub const TILE_WIDTH_HALF: f32 = TILE_WIDTH / 2.0;
-pub const TILE_HEIGHT_HALF: f32 = TILE_HEIGHT / 2.0;
-
-pub type MapTiles = [[Tile; 11]; 13];
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub enum TileStatus {
-    Free,
-    Wall,
-    PermanentWall,
-}
-
-#[derive(Clone, Copy, Debug, PartialEq)]
-pub struct Tile {
-    pub status: TileStatus,
-    pub coordinates: [usize; 2],
-}
-
-impl Component for Tile {
-    type Storage = DenseVecStorage<Self>;
-}
+use crate::editor::MapEditorState;
+use crate::entities::hud::{self, HUD_HEIGHT};
+use crate::entities::player::{self, Controls, MAX_HUMAN_PLAYERS, MAX_TEAMS};
+use crate::entities::power_up;
+use crate::entities::tile::{self, Tile};
+use crate::net::{self, NetClient, NetHost};
+use crate::replay::{ReplayPlayer, ReplayRecorder};
+use crate::rules;
+use crate::sim::generator::{self, GeneratorSettings, Symmetry};
+use crate::sim::map::{TILE_HEIGHT, TILE_HEIGHT_HALF, TILE_WIDTH, TILE_WIDTH_HALF};
+use crate::sim::{
+    DeathCause, GameEvent, GameRules, Map, Match, RoundOutcome, Side, Simulation, TICKS_PER_SECOND,
+};
+use crate::stats::{MatchStats, STATS_DIR};
 
 #[derive(Copy, Clone, Eq, Hash, PartialEq)]
 pub enum AssetType {
     Bomb,
     Explosion,
+    PowerUp,
 }
 
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct SpriteSheetList {
     sprite_sheets: HashMap<AssetType, SpriteSheetHandle>,
 }
@@ -72,73 +60,247 @@ impl SpriteSheetList {
     }
 }
 
-pub struct Map {
-    tiles: MapTiles,
+/// Everything the `Simulation` reported during the last frame.
+#[derive(Default)]
+pub struct GameEvents {
+    pub events: Vec<GameEvent>,
 }
 
-impl Component for Map {
-    type Storage = DenseVecStorage<Self>;
+#[derive(PartialEq)]
+pub enum CurrentState {
+    Running,
+    Paused,
+    Results,
 }
 
-impl Map {
-    pub fn get_tile(&self, x: f32, y: f32) -> Tile {
-        let grid_x = (x / (ARENA_WIDTH / TILE_COUNT_HORIZONTAL)).floor() as usize;
-        let grid_y = (y / (ARENA_HEIGHT / TILE_COUNT_VERTICAL)).floor() as usize;
-        self.tiles[grid_x][grid_y]
+impl Default for CurrentState {
+    fn default() -> Self {
+        CurrentState::Paused
     }
+}
 
-    pub fn get_tile_by_key(&self, x: usize, y: usize) -> Tile {
-        self.tiles[x][y]
-    }
+/// Frame time not yet turned into simulation ticks.
+#[derive(Default)]
+pub struct GameTimeController {
+    pub accumulator: Duration,
+}
 
-    pub fn update_tile(&mut self, x: usize, y: usize, status: TileStatus) {
-        self.tiles[x][y].status = status;
-    }
+/// Draws the seed of every round, so a whole match plays out the same from the
+/// same first seed.
+pub struct GameRng(pub StdRng);
+
+/// How a match is played, as chosen in the `MainMenuState`.
+#[derive(Clone)]
+pub struct MatchSettings {
+    pub map_path: String,
+    /// Every round is played on a new map drawn from these settings, in
+    /// place of the one at `map_path`.
+    pub generator: Option<GeneratorSettings>,
+    pub humans: u8,
+    pub bots: u8,
+    /// Name of the preset the `GameRules` resource was last loaded from.
+    pub rules_preset: String,
+    pub rounds_to_win: u8,
+    /// Teams the players are split into, a free-for-all below two.
+    pub teams: u8,
+    /// Whether the flames of a player hurt their teammates.
+    pub friendly_fire: bool,
+    /// The rounds are drawn from this seed, a random one unless given.
+    pub seed: Option<u64>,
 }
 
-impl Default for Map {
+/// Players a match needs, humans and bots together, for a round to be won.
+pub const MIN_PLAYERS: u8 = 2;
+
+impl Default for MatchSettings {
     fn default() -> Self {
-        Map {
-            tiles: [[Tile {
-                status: TileStatus::Free,
-                coordinates: [0, 0],
-            }; 11]; 13],
+        MatchSettings {
+            map_path: "resources/maps/default.txt".to_string(),
+            generator: None,
+            humans: 1,
+            bots: 3,
+            rules_preset: rules::DEFAULT_PRESET.to_string(),
+            rounds_to_win: 2,
+            teams: 0,
+            friendly_fire: true,
+            seed: None,
         }
     }
 }
 
-#[derive(PartialEq)]
-pub enum CurrentState {
-    Running,
-    Paused,
-    Results,
+pub struct GameplayState {
+    settings: MatchSettings,
+    /// Played instead of the file at `settings.map_path` when given.
+    map: Option<Map>,
+    /// What the round being played was started with.
+    round_setup: Option<RoundSetup>,
+    /// The map of the next round failed to load, the match is called off.
+    map_failed: bool,
 }
 
-impl Default for CurrentState {
-    fn default() -> Self {
-        CurrentState::Paused
-    }
+/// Everything a round starts from, to play it again.
+#[derive(Clone)]
+struct RoundSetup {
+    map: Map,
+    seed: u64,
+    tick_limit: u32,
+    teams: u8,
+    rules: GameRules,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PauseItem {
+    Resume,
+    RestartRound,
+    Settings,
+    QuitToMenu,
+    MusicVolume,
+    EffectsVolume,
+    Back,
 }
 
+const SETTINGS_ITEMS: [PauseItem; 3] = [
+    PauseItem::MusicVolume,
+    PauseItem::EffectsVolume,
+    PauseItem::Back,
+];
+
+/// Left behind by the `PausedState` for the `GameplayState` to start the round
+/// over once it resumes.
+struct RestartRound;
+
+/// Freezes the game under a menu to resume it, start the round over, change
+/// the volumes or quit to the main menu.
 #[derive(Default)]
-pub struct GameTimeController {
-    pub stopwatch: Stopwatch,
-    pub max_time: Duration,
+pub struct PausedState {
+    items: Vec<PauseItem>,
+    selected: usize,
+    labels: Vec<Entity>,
 }
 
-pub struct GameplayState;
-pub struct PausedState;
-pub struct ResultsState;
+/// Columns of the statistics shown after every round.
+const STATS_COLUMNS: [&str; 8] = [
+    "", "Kills", "Suicides", "Bombs", "Walls", "Items", "Alive", "Round",
+];
+
+/// Shows who won the round that just ended, the wins of every side and the
+/// statistics of every player over the match.
+pub struct ResultsState {
+    outcome: RoundOutcome,
+    labels: Vec<Entity>,
+}
 
 impl SimpleState for ResultsState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        *data.world.write_resource::<CurrentState>() = CurrentState::Results;
+        let world = data.world;
+
+        let game_match = (*world.read_resource::<Match>()).clone();
+        let mut sides: Vec<Side> = Vec::new();
+        for player in &world.read_resource::<Simulation>().players {
+            if !sides.contains(&player.side()) {
+                sides.push(player.side());
+            }
+        }
+        sides.sort();
+        let round = game_match.rounds_played();
+        let mut lines = vec![match self.outcome {
+            RoundOutcome::Winner(side) => format!("{} wins round {}", side, round),
+            RoundOutcome::Draw => format!("Round {} is a draw", round),
+        }];
+        lines.push(
+            sides
+                .iter()
+                .map(|side| format!("{}: {}", side.short_name(), game_match.wins(*side)))
+                .collect::<Vec<_>>()
+                .join("   "),
+        );
+        match game_match.winner() {
+            Some(side) => {
+                lines.push(format!("{} wins the match!", side));
+                lines.push("Press Enter for a new match".to_string());
+            }
+            None => lines.push("Press Enter for the next round".to_string()),
+        }
+        let replay_over = world
+            .try_fetch::<ReplayPlayer>()
+            .map_or(false, |player| player.is_last_round());
+        if replay_over {
+            lines.pop();
+            lines.push("End of the replay, press Enter to quit".to_string());
+        }
+
+        let font = {
+            let loader = world.read_resource::<Loader>();
+            let font_storage = world.read_resource::<AssetStorage<FontAsset>>();
+            get_default_font(&loader, &font_storage)
+        };
+        for (i, line) in lines.into_iter().enumerate() {
+            let transform = UiTransform::new(
+                format!("results_{}", i),
+                Anchor::Middle,
+                Anchor::Middle,
+                0.,
+                200. - i as f32 * 40.,
+                1.,
+                600.,
+                40.,
+            );
+            let text = UiText::new(font.clone(), line, [1., 1., 1., 1.], 28.);
+            let label = world.create_entity().with(transform).with(text).build();
+            self.labels.push(label);
+        }
+
+        let rows = stats_rows(world);
+        let width = 600. / STATS_COLUMNS.len() as f32;
+        for (row, cells) in rows.into_iter().enumerate() {
+            for (column, cell) in cells.into_iter().enumerate() {
+                let transform = UiTransform::new(
+                    format!("results_stats_{}_{}", row, column),
+                    Anchor::Middle,
+                    Anchor::Middle,
+                    (column as f32 + 0.5) * width - 300.,
+                    30. - row as f32 * 22.,
+                    1.,
+                    width,
+                    22.,
+                );
+                let text = UiText::new(font.clone(), cell, [1., 1., 1., 1.], 14.);
+                let label = world.create_entity().with(transform).with(text).build();
+                self.labels.push(label);
+            }
+        }
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        data.world
+            .delete_entities(&self.labels)
+            .expect("failed to delete the results");
+        self.labels.clear();
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        // clients move on once the host has started the next round
+        match host_round(data.world) {
+            Some(round) if round != data.world.read_resource::<Simulation>().round => Trans::Pop,
+            _ => Trans::None,
+        }
+    }
+
     fn handle_event(
         &mut self,
         data: StateData<'_, GameData<'_, '_>>,
         event: StateEvent,
     ) -> SimpleTrans {
         if let StateEvent::Window(event) = &event {
-            if is_key_down(&event, VirtualKeyCode::Return) {
-                *data.world.write_resource::<CurrentState>() = CurrentState::Results;
+            if is_key_down(&event, VirtualKeyCode::Return) && !data.world.has_value::<NetClient>() {
+                let replay_over = data
+                    .world
+                    .try_fetch::<ReplayPlayer>()
+                    .map_or(false, |player| player.is_last_round());
+                if replay_over {
+                    return Trans::Quit;
+                }
                 return Trans::Pop;
             }
         }
@@ -147,19 +309,713 @@ impl SimpleState for ResultsState {
     }
 }
 
+/// The cells of the statistics table, headers first, then a row per player:
+/// their stats over the match and how the round went for them.
+fn stats_rows(world: &World) -> Vec<Vec<String>> {
+    let simulation = world.read_resource::<Simulation>();
+    let match_stats = world.read_resource::<MatchStats>();
+    let mut rows = vec![STATS_COLUMNS.iter().map(|cell| cell.to_string()).collect()];
+    for player in &simulation.players {
+        let stats = match_stats
+            .stats
+            .players
+            .get(&player.number)
+            .cloned()
+            .unwrap_or_default();
+        let seconds = stats.ticks_alive / TICKS_PER_SECOND;
+        let round = match simulation
+            .stats
+            .death(player.number)
+            .map(|death| death.cause)
+        {
+            None => "alive".to_string(),
+            Some(DeathCause::Bomb(owner)) if owner == player.number => "own bomb".to_string(),
+            Some(DeathCause::Bomb(owner)) => format!("by P{}", owner + 1),
+            Some(DeathCause::Crushed) => "crushed".to_string(),
+        };
+        rows.push(vec![
+            format!("P{}", player.number + 1),
+            stats.kills.to_string(),
+            stats.suicides.to_string(),
+            stats.bombs_placed.to_string(),
+            stats.walls_destroyed.to_string(),
+            stats.power_ups_collected.to_string(),
+            format!("{}:{:02}", seconds / 60, seconds % 60),
+            round,
+        ]);
+    }
+    rows
+}
+
+impl PausedState {
+    /// The entries of the pause menu. Clients follow the round of the host
+    /// and replays the recorded ones, neither can start a round over.
+    fn pause_items(world: &World) -> Vec<PauseItem> {
+        let restartable = !world.has_value::<NetClient>() && !world.has_value::<ReplayPlayer>();
+        let mut items = vec![PauseItem::Resume];
+        if restartable {
+            items.push(PauseItem::RestartRound);
+        }
+        items.extend(&[PauseItem::Settings, PauseItem::QuitToMenu]);
+        items
+    }
+
+    fn lines(&self, world: &World) -> Vec<String> {
+        let audio_settings = world.read_resource::<AudioSettings>();
+        self.items
+            .iter()
+            .map(|item| match item {
+                PauseItem::Resume => "Resume".to_string(),
+                PauseItem::RestartRound => "Restart round".to_string(),
+                PauseItem::Settings => "Settings".to_string(),
+                PauseItem::QuitToMenu => "Quit to menu".to_string(),
+                PauseItem::MusicVolume => format!(
+                    "Music volume: {}",
+                    volume_label(&audio_settings, audio_settings.music_volume)
+                ),
+                PauseItem::EffectsVolume => format!(
+                    "Effects volume: {}",
+                    volume_label(&audio_settings, audio_settings.effects_volume)
+                ),
+                PauseItem::Back => "Back".to_string(),
+            })
+            .collect()
+    }
+
+    /// Shows `items` over the frozen game, the first one selected.
+    fn show(&mut self, world: &mut World, items: Vec<PauseItem>) {
+        self.hide(world);
+        self.items = items;
+        self.selected = 0;
+        let font = {
+            let loader = world.read_resource::<Loader>();
+            let font_storage = world.read_resource::<AssetStorage<FontAsset>>();
+            get_default_font(&loader, &font_storage)
+        };
+        // dims the game under the menu
+        let background = UiTransform::new(
+            "pause_background".to_string(),
+            Anchor::Middle,
+            Anchor::Middle,
+            0.,
+            0.,
+            2.,
+            1.,
+            1.,
+        )
+        .into_percent();
+        self.labels.push(
+            world
+                .create_entity()
+                .with(background)
+                .with(UiImage::SolidColor([0., 0., 0., 0.6]))
+                .build(),
+        );
+        let title = UiTransform::new(
+            "pause_title".to_string(),
+            Anchor::Middle,
+            Anchor::Middle,
+            0.,
+            120.,
+            3.,
+            600.,
+            60.,
+        );
+        let text = UiText::new(font.clone(), "Paused".to_string(), [1., 1., 1., 1.], 48.);
+        self.labels
+            .push(world.create_entity().with(title).with(text).build());
+        for (i, line) in self.lines(world).into_iter().enumerate() {
+            let transform = UiTransform::new(
+                format!("pause_{}", i),
+                Anchor::Middle,
+                Anchor::Middle,
+                0.,
+                40. - i as f32 * 40.,
+                3.,
+                600.,
+                40.,
+            );
+            let text = UiText::new(font.clone(), line, [1., 1., 1., 1.], 28.);
+            let label = world.create_entity().with(transform).with(text).build();
+            self.labels.push(label);
+        }
+        self.refresh(world);
+    }
+
+    /// Updates the labels after a change, highlighting the selected one.
+    fn refresh(&self, world: &mut World) {
+        let lines = self.lines(world);
+        let mut texts = world.write_storage::<UiText>();
+        // the first labels are the background and the title
+        for (i, (line, label)) in lines.into_iter().zip(&self.labels[2..]).enumerate() {
+            if let Some(text) = texts.get_mut(*label) {
+                if i == self.selected {
+                    text.text = format!("< {} >", line);
+                    text.color = [1., 0.85, 0.2, 1.];
+                } else {
+                    text.text = line;
+                    text.color = [1., 1., 1., 1.];
+                }
+            }
+        }
+    }
+
+    fn hide(&mut self, world: &mut World) {
+        world
+            .delete_entities(&self.labels)
+            .expect("failed to delete the pause menu");
+        self.labels.clear();
+    }
+
+    fn change(&mut self, world: &World, item: PauseItem, direction: i32) {
+        {
+            let mut audio_settings = world.write_resource::<AudioSettings>();
+            match item {
+                PauseItem::MusicVolume => {
+                    audio_settings.music_volume =
+                        step_volume(audio_settings.music_volume, direction)
+                }
+                PauseItem::EffectsVolume => {
+                    audio_settings.effects_volume =
+                        step_volume(audio_settings.effects_volume, direction)
+                }
+                _ => return,
+            }
+        }
+        audio::apply_music_volume(world);
+    }
+}
+
 impl SimpleState for PausedState {
     fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
         *data.world.write_resource::<CurrentState>() = CurrentState::Paused;
+        let items = PausedState::pause_items(data.world);
+        self.show(data.world, items);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.hide(data.world);
     }
+
     fn handle_event(
         &mut self,
-        _data: StateData<'_, GameData<'_, '_>>,
+        data: StateData<'_, GameData<'_, '_>>,
         event: StateEvent,
     ) -> SimpleTrans {
         if let StateEvent::Window(event) = &event {
-            if is_key_down(&event, VirtualKeyCode::P) {
+            if is_close_requested(&event) {
+                return Trans::Quit;
+            }
+            let back = is_key_down(&event, VirtualKeyCode::Escape)
+                || is_key_down(&event, VirtualKeyCode::P);
+            if back {
+                // out of the settings first, then out of the pause menu
+                if self.items[..] == SETTINGS_ITEMS[..] {
+                    let items = PausedState::pause_items(data.world);
+                    self.show(data.world, items);
+                    return Trans::None;
+                }
                 return Trans::Pop;
             }
+            let item = self.items[self.selected];
+            if is_key_down(&event, VirtualKeyCode::Return) {
+                match item {
+                    PauseItem::Resume => return Trans::Pop,
+                    PauseItem::RestartRound => {
+                        data.world.insert(RestartRound);
+                        return Trans::Pop;
+                    }
+                    PauseItem::Settings => {
+                        self.show(data.world, SETTINGS_ITEMS.to_vec());
+                        return Trans::None;
+                    }
+                    PauseItem::Back => {
+                        let items = PausedState::pause_items(data.world);
+                        self.show(data.world, items);
+                        return Trans::None;
+                    }
+                    // back to whatever the game was started from
+                    PauseItem::QuitToMenu => return Trans::Sequence(vec![Trans::Pop, Trans::Pop]),
+                    _ => {}
+                }
+            }
+            if is_key_down(&event, VirtualKeyCode::Up) {
+                self.selected = (self.selected + self.items.len() - 1) % self.items.len();
+            } else if is_key_down(&event, VirtualKeyCode::Down) {
+                self.selected = (self.selected + 1) % self.items.len();
+            } else if is_key_down(&event, VirtualKeyCode::Left) {
+                self.change(data.world, item, -1);
+            } else if is_key_down(&event, VirtualKeyCode::Right) {
+                self.change(data.world, item, 1);
+            } else {
+                return Trans::None;
+            }
+            self.refresh(data.world);
+        }
+
+        Trans::None
+    }
+}
+
+/// Turns a volume up or down a notch, `direction` being 1 or -1.
+fn step_volume(volume: f32, direction: i32) -> f32 {
+    (volume + direction as f32 * 0.1).max(0.).min(1.)
+}
+
+fn volume_label(audio_settings: &AudioSettings, volume: f32) -> String {
+    if audio_settings.enabled {
+        format!("{}%", (volume * 100.).round())
+    } else {
+        "off".to_string()
+    }
+}
+
+/// Seconds a round can be set to last.
+const ROUND_TIMES: [u32; 6] = [60, 90, 120, 180, 240, 300];
+const MAX_ROUNDS_TO_WIN: u8 = 9;
+
+/// A map the menu offers to play on.
+struct MapChoice {
+    source: MapSource,
+    name: String,
+    spawn_points: usize,
+}
+
+enum MapSource {
+    File(String),
+    Generated(GeneratorSettings),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum MenuItem {
+    Map,
+    Humans,
+    Bots,
+    Rules,
+    RoundTime,
+    RoundsToWin,
+    Teams,
+    FriendlyFire,
+    MusicVolume,
+    EffectsVolume,
+    Start,
+    EditMap,
+    NewMap,
+}
+
+const MENU_ITEMS: [MenuItem; 13] = [
+    MenuItem::Map,
+    MenuItem::Humans,
+    MenuItem::Bots,
+    MenuItem::Rules,
+    MenuItem::RoundTime,
+    MenuItem::RoundsToWin,
+    MenuItem::Teams,
+    MenuItem::FriendlyFire,
+    MenuItem::MusicVolume,
+    MenuItem::EffectsVolume,
+    MenuItem::Start,
+    MenuItem::EditMap,
+    MenuItem::NewMap,
+];
+
+/// Lets the players set up the match before starting it. Up and down pick a
+/// setting, left and right change it.
+pub struct MainMenuState {
+    settings: MatchSettings,
+    maps: Vec<MapChoice>,
+    map_index: usize,
+    selected: usize,
+    labels: Vec<Entity>,
+}
+
+impl MainMenuState {
+    pub fn new(settings: MatchSettings) -> Self {
+        MainMenuState {
+            settings,
+            maps: Vec::new(),
+            map_index: 0,
+            selected: MENU_ITEMS
+                .iter()
+                .position(|item| *item == MenuItem::Start)
+                .unwrap_or(0),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Every map of `resources/maps` which loads, sorted by file name.
+    fn load_maps(&mut self) {
+        let mut paths: Vec<String> = fs::read_dir("resources/maps")
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().map_or(false, |ext| ext == "txt"))
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        paths.sort();
+        self.maps = paths
+            .into_iter()
+            .filter_map(|path| match read_map(&path) {
+                Ok(map) => Some(MapChoice {
+                    name: if map.name.is_empty() {
+                        path.clone()
+                    } else {
+                        map.name
+                    },
+                    source: MapSource::File(path),
+                    spawn_points: map.spawn_points.len(),
+                }),
+                Err(err) => {
+                    warn!("skipping the map {}: {}", path, err);
+                    None
+                }
+            })
+            .collect();
+        for (name, symmetry) in &[
+            ("Random", Symmetry::Mirror),
+            ("Random, rotated", Symmetry::Rotational),
+            ("Random, asymmetric", Symmetry::None),
+        ] {
+            self.maps.push(MapChoice {
+                source: MapSource::Generated(GeneratorSettings {
+                    symmetry: *symmetry,
+                    ..GeneratorSettings::default()
+                }),
+                name: name.to_string(),
+                spawn_points: generator::SPAWN_POINTS,
+            });
+        }
+        let settings = &self.settings;
+        let index = self
+            .maps
+            .iter()
+            .position(|map| match (&map.source, &settings.generator) {
+                (MapSource::File(path), None) => path.ends_with(&settings.map_path),
+                (MapSource::Generated(generator), Some(other)) => {
+                    generator.symmetry == other.symmetry
+                }
+                _ => false,
+            });
+        self.map_index = match index {
+            Some(index) => index,
+            // a map from elsewhere, given on the command line
+            None if settings.generator.is_none() => match read_map(&settings.map_path) {
+                Ok(map) => {
+                    self.maps.push(MapChoice {
+                        name: if map.name.is_empty() {
+                            settings.map_path.clone()
+                        } else {
+                            map.name
+                        },
+                        source: MapSource::File(settings.map_path.clone()),
+                        spawn_points: map.spawn_points.len(),
+                    });
+                    self.maps.len() - 1
+                }
+                Err(err) => {
+                    warn!("skipping the map {}: {}", settings.map_path, err);
+                    0
+                }
+            },
+            None => 0,
+        };
+    }
+
+    /// Whether the snapshots of a round on the map picked fit in what the host
+    /// sends to its clients.
+    fn fits_online(&self, world: &World) -> bool {
+        let map = match &self.settings.generator {
+            Some(generator) => generator::generate(generator, 0),
+            None => match read_map(&self.settings.map_path) {
+                Ok(map) => map,
+                // failing to load is reported when the round starts
+                Err(_) => return true,
+            },
+        };
+        net::fits(&map, &world.read_resource::<GameRules>())
+    }
+
+    /// Keeps the number of players within what the map and the keyboard allow.
+    fn clamp_players(&mut self) {
+        let spawn_points = self
+            .maps
+            .get(self.map_index)
+            .map_or(MAX_HUMAN_PLAYERS as usize, |map| map.spawn_points)
+            .min(u8::max_value() as usize) as u8;
+        let settings = &mut self.settings;
+        settings.humans = settings.humans.min(MAX_HUMAN_PLAYERS).min(spawn_points);
+        settings.bots = settings.bots.min(spawn_points - settings.humans);
+        // bots make up for the missing players, as far as the map allows
+        let missing = MIN_PLAYERS.saturating_sub(settings.humans + settings.bots);
+        settings.bots = (settings.bots + missing).min(spawn_points - settings.humans);
+    }
+
+    /// Moves the setting at `item` one step in `direction`, -1 or 1.
+    fn change(&mut self, world: &World, item: MenuItem, direction: i32) {
+        let step = |value: u8, min: u8, max: u8| {
+            (i32::from(value) + direction)
+                .max(i32::from(min))
+                .min(i32::from(max)) as u8
+        };
+        let settings = &mut self.settings;
+        match item {
+            MenuItem::Map => {
+                let count = self.maps.len() as i32;
+                self.map_index = (self.map_index as i32 + direction).rem_euclid(count) as usize;
+                match &self.maps[self.map_index].source {
+                    MapSource::File(path) => {
+                        settings.map_path = path.clone();
+                        settings.generator = None;
+                    }
+                    MapSource::Generated(generator) => settings.generator = Some(generator.clone()),
+                }
+            }
+            MenuItem::Humans => settings.humans = step(settings.humans, 0, MAX_HUMAN_PLAYERS),
+            MenuItem::Bots => settings.bots = step(settings.bots, 0, u8::max_value() - 1),
+            MenuItem::Rules => {
+                let presets = rules::presets();
+                if !presets.is_empty() {
+                    let count = presets.len() as i32;
+                    let index = presets
+                        .iter()
+                        .position(|name| *name == settings.rules_preset)
+                        .map_or(0, |index| (index as i32 + direction).rem_euclid(count));
+                    let name = &presets[index as usize];
+                    match rules::load_preset(name) {
+                        Ok(game_rules) => {
+                            *world.write_resource::<GameRules>() = game_rules;
+                            settings.rules_preset = name.clone();
+                        }
+                        Err(err) => error!("failed to load the rules {}: {}", name, err),
+                    }
+                }
+            }
+            MenuItem::RoundTime => {
+                let mut game_rules = world.write_resource::<GameRules>();
+                let index = ROUND_TIMES
+                    .iter()
+                    .position(|time| *time >= game_rules.round_time)
+                    .unwrap_or(ROUND_TIMES.len() - 1) as i32;
+                let index = (index + direction).max(0).min(ROUND_TIMES.len() as i32 - 1);
+                game_rules.round_time = ROUND_TIMES[index as usize];
+            }
+            MenuItem::RoundsToWin => {
+                settings.rounds_to_win = step(settings.rounds_to_win, 1, MAX_ROUNDS_TO_WIN)
+            }
+            MenuItem::Teams => {
+                // a single team is no different from a free-for-all
+                settings.teams = match step(settings.teams, 0, MAX_TEAMS) {
+                    1 if direction > 0 => 2,
+                    1 => 0,
+                    teams => teams,
+                }
+            }
+            MenuItem::FriendlyFire => settings.friendly_fire = !settings.friendly_fire,
+            MenuItem::MusicVolume => {
+                let mut audio_settings = world.write_resource::<AudioSettings>();
+                audio_settings.music_volume = step_volume(audio_settings.music_volume, direction);
+            }
+            MenuItem::EffectsVolume => {
+                let mut audio_settings = world.write_resource::<AudioSettings>();
+                audio_settings.effects_volume =
+                    step_volume(audio_settings.effects_volume, direction);
+            }
+            _ => {}
+        }
+        self.clamp_players();
+        audio::apply_music_volume(world);
+    }
+
+    fn lines(&self, world: &World) -> Vec<String> {
+        let settings = &self.settings;
+        let audio_settings = world.read_resource::<AudioSettings>();
+        let game_rules = world.read_resource::<GameRules>();
+        let map = self
+            .maps
+            .get(self.map_index)
+            .map_or(settings.map_path.as_str(), |map| map.name.as_str());
+        MENU_ITEMS
+            .iter()
+            .map(|item| match item {
+                MenuItem::Map => format!("Map: {}", map),
+                MenuItem::Humans => format!("Humans: {}", settings.humans),
+                MenuItem::Bots => format!("Bots: {}", settings.bots),
+                MenuItem::Rules => format!("Rules: {}", settings.rules_preset),
+                MenuItem::RoundTime => format!(
+                    "Round time: {}:{:02}",
+                    game_rules.round_time / 60,
+                    game_rules.round_time % 60
+                ),
+                MenuItem::RoundsToWin => format!("Rounds to win: {}", settings.rounds_to_win),
+                MenuItem::Teams if settings.teams < 2 => "Teams: free-for-all".to_string(),
+                MenuItem::Teams => format!("Teams: {}", settings.teams),
+                MenuItem::FriendlyFire => format!(
+                    "Friendly fire: {}",
+                    if settings.friendly_fire { "on" } else { "off" }
+                ),
+                MenuItem::MusicVolume => {
+                    format!(
+                        "Music volume: {}",
+                        volume_label(&audio_settings, audio_settings.music_volume)
+                    )
+                }
+                MenuItem::EffectsVolume => {
+                    format!(
+                        "Effects volume: {}",
+                        volume_label(&audio_settings, audio_settings.effects_volume)
+                    )
+                }
+                MenuItem::Start => "Start".to_string(),
+                MenuItem::EditMap => "Edit map".to_string(),
+                MenuItem::NewMap => "New map".to_string(),
+            })
+            .collect()
+    }
+
+    fn show(&mut self, world: &mut World) {
+        let font = {
+            let loader = world.read_resource::<Loader>();
+            let font_storage = world.read_resource::<AssetStorage<FontAsset>>();
+            get_default_font(&loader, &font_storage)
+        };
+        let title = UiTransform::new(
+            "menu_title".to_string(),
+            Anchor::Middle,
+            Anchor::Middle,
+            0.,
+            220.,
+            1.,
+            600.,
+            60.,
+        );
+        let text = UiText::new(font.clone(), "Bomberman".to_string(), [1., 1., 1., 1.], 48.);
+        self.labels
+            .push(world.create_entity().with(title).with(text).build());
+        for (i, line) in self.lines(world).into_iter().enumerate() {
+            let transform = UiTransform::new(
+                format!("menu_{}", i),
+                Anchor::Middle,
+                Anchor::Middle,
+                0.,
+                140. - i as f32 * 40.,
+                1.,
+                600.,
+                40.,
+            );
+            let text = UiText::new(font.clone(), line, [1., 1., 1., 1.], 28.);
+            let label = world.create_entity().with(transform).with(text).build();
+            self.labels.push(label);
+        }
+        self.refresh(world);
+    }
+
+    /// Updates the labels after a change, highlighting the selected one.
+    fn refresh(&self, world: &mut World) {
+        let lines = self.lines(world);
+        let mut texts = world.write_storage::<UiText>();
+        // the first label is the title
+        for (i, (line, label)) in lines.into_iter().zip(&self.labels[1..]).enumerate() {
+            if let Some(text) = texts.get_mut(*label) {
+                if i == self.selected {
+                    text.text = format!("< {} >", line);
+                    text.color = [1., 0.85, 0.2, 1.];
+                } else {
+                    text.text = line;
+                    text.color = [1., 1., 1., 1.];
+                }
+            }
+        }
+    }
+
+    fn hide(&mut self, world: &mut World) {
+        world
+            .delete_entities(&self.labels)
+            .expect("failed to delete the menu");
+        self.labels.clear();
+    }
+}
+
+impl SimpleState for MainMenuState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.load_maps();
+        self.clamp_players();
+        audio::apply_music_volume(data.world);
+        self.show(data.world);
+    }
+
+    fn on_pause(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.hide(data.world);
+    }
+
+    fn on_resume(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        // maps may have been edited in the meantime
+        self.load_maps();
+        self.clamp_players();
+        self.show(data.world);
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        self.hide(data.world);
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        // clients play what the host chose, replays what was recorded
+        let configured =
+            data.world.has_value::<NetClient>() || data.world.has_value::<ReplayPlayer>();
+        if configured {
+            return Trans::Switch(Box::new(GameplayState::new(self.settings.clone())));
+        }
+        Trans::None
+    }
+
+    fn handle_event(
+        &mut self,
+        data: StateData<'_, GameData<'_, '_>>,
+        event: StateEvent,
+    ) -> SimpleTrans {
+        if let StateEvent::Window(event) = &event {
+            if is_close_requested(&event) || is_key_down(&event, VirtualKeyCode::Escape) {
+                return Trans::Quit;
+            }
+            let item = MENU_ITEMS[self.selected];
+            if is_key_down(&event, VirtualKeyCode::Return) {
+                match item {
+                    MenuItem::Start
+                        if data.world.has_value::<NetHost>() && !self.fits_online(data.world) =>
+                    {
+                        error!("the map is too large to be played online")
+                    }
+                    MenuItem::Start => {
+                        return Trans::Push(Box::new(GameplayState::new(self.settings.clone())))
+                    }
+                    MenuItem::EditMap if self.settings.generator.is_some() => {
+                        let generator = self.settings.generator.as_ref().unwrap();
+                        let editor = MapEditorState::generate(generator, rand::random());
+                        return Trans::Push(Box::new(editor));
+                    }
+                    MenuItem::EditMap => match MapEditorState::open(&self.settings.map_path) {
+                        Ok(editor) => return Trans::Push(Box::new(editor)),
+                        Err(err) => error!("failed to open {}: {}", self.settings.map_path, err),
+                    },
+                    MenuItem::NewMap => return Trans::Push(Box::new(MapEditorState::create())),
+                    _ => {}
+                }
+            }
+            if is_key_down(&event, VirtualKeyCode::Up) {
+                self.selected = (self.selected + MENU_ITEMS.len() - 1) % MENU_ITEMS.len();
+            } else if is_key_down(&event, VirtualKeyCode::Down) {
+                self.selected = (self.selected + 1) % MENU_ITEMS.len();
+            } else if is_key_down(&event, VirtualKeyCode::Left) {
+                self.change(data.world, item, -1);
+            } else if is_key_down(&event, VirtualKeyCode::Right) {
+                self.change(data.world, item, 1);
+            } else {
+                return Trans::None;
+            }
+            self.refresh(data.world);
         }
 
         Trans::None
@@ -174,64 +1030,148 @@ impl SimpleState for GameplayState {
         *data.world.write_resource::<CurrentState>() = CurrentState::Running;
         let world = data.world;
 
-        let tiles = read_map("resources/maps/default.txt").unwrap();
-        world.insert(Map { tiles });
-
-        // Get the screen dimensions so we can initialize the camera and
-        // place our sprites correctly later. We'll clone this since we'll
-        // pass the world mutably to the following functions.
-        let dimensions = (*world.read_resource::<ScreenDimensions>()).clone();
-
-        // Place the camera
-        init_camera(world, &dimensions);
-
-        // Load our sprites and display them
         let sprite_sheet_list = load_sprites(world);
-        let sprites: Vec<SpriteRender> = (0..5)
-            .map(|i| SpriteRender {
-                sprite_sheet: sprite_sheet_list.get(AssetType::Bomb).unwrap().clone(),
-                sprite_number: i,
-            })
-            .collect();
-        init_sprites_map(world, &tiles, &sprites, &dimensions);
-        player::init_players(world, &sprites);
         world.insert(sprite_sheet_list);
+        if world.read_resource::<AudioSettings>().enabled {
+            let sounds = audio::load_sounds(world);
+            world.insert(sounds);
+        }
+        let controls = if let Some(client) = world.try_fetch::<NetClient>() {
+            Controls::for_player(client.number())
+        } else if world.has_value::<ReplayPlayer>() {
+            Controls::new(0)
+        } else {
+            Controls::new(self.settings.humans)
+        };
+        world.insert(controls);
+        if let Some(player) = world.try_fetch::<ReplayPlayer>() {
+            self.settings.seed = Some(player.replay.seed);
+            self.settings.rounds_to_win = player.replay.rounds_to_win;
+            self.settings.teams = player.replay.teams;
+            self.settings.friendly_fire = player.replay.friendly_fire;
+        }
+        let replayed_rules = world
+            .try_fetch::<ReplayPlayer>()
+            .map(|player| player.replay.rules.clone());
+        if let Some(game_rules) = replayed_rules {
+            world.insert(game_rules);
+        }
+        if let Some(client) = world.try_fetch::<NetClient>() {
+            self.settings.rounds_to_win = client.rounds_to_win();
+        }
+        if let Some(mut host) = world.try_fetch_mut::<NetHost>() {
+            host.rounds_to_win = self.settings.rounds_to_win;
+        }
+        world.insert(Match::new(self.settings.rounds_to_win));
+        world.insert(MatchStats::default());
+        let seed = self.settings.seed.unwrap_or_else(rand::random);
+        info!("seed: {}", seed);
+        world.insert(GameRng(StdRng::seed_from_u64(seed)));
+        if let Some(mut recorder) = world.try_fetch_mut::<ReplayRecorder>() {
+            recorder.replay.map_path = self.settings.map_path.clone();
+            recorder.replay.seed = seed;
+            recorder.replay.rounds_to_win = self.settings.rounds_to_win;
+            recorder.replay.teams = self.settings.teams;
+            recorder.replay.friendly_fire = self.settings.friendly_fire;
+            recorder.replay.rules = (*world.read_resource::<GameRules>()).clone();
+        }
+        self.next_round(world);
+    }
 
-        let mut stopwatch = Stopwatch::new();
-        stopwatch.start();
-        world.insert(GameTimeController {
-            stopwatch: stopwatch.clone(),
-            max_time: Duration::new(180, 0),
-        });
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        save_replay(data.world);
+        if let Some(mut music) = data.world.try_fetch_mut::<Music>() {
+            music.stop();
+        }
+        data.world.delete_all();
+        *data.world.write_resource::<CurrentState>() = CurrentState::Paused;
     }
 
-    fn on_pause(&mut self, data: StateData<'_, GameData<'_, '_>>) {
-        let game_time_controller = &mut *data.world.write_resource::<GameTimeController>();
-        game_time_controller.stopwatch.stop();
-        info!("on_pause {:?}", game_time_controller.stopwatch.elapsed());
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        // back to the menu
+        if self.map_failed {
+            return Trans::Pop;
+        }
+        if *data.world.read_resource::<CurrentState>() != CurrentState::Running {
+            return Trans::None;
+        }
+        if let Some(round) = host_round(data.world) {
+            // we missed the end of the round the host already left behind
+            if round != data.world.read_resource::<Simulation>().round {
+                self.next_round(data.world);
+                return Trans::None;
+            }
+        }
+        let outcome = match data.world.read_resource::<Simulation>().outcome() {
+            Some(outcome) => outcome,
+            None => return Trans::None,
+        };
+        data.world.write_resource::<Match>().record(outcome);
+        info!("round over: {:?}", outcome);
+        data.world
+            .write_resource::<MatchStats>()
+            .record(&data.world.read_resource::<Simulation>());
+        save_replay(data.world);
+        // replays would only write the same statistics again
+        let match_over = data.world.read_resource::<Match>().winner().is_some();
+        if match_over && !data.world.has_value::<ReplayPlayer>() {
+            let game_match = data.world.read_resource::<Match>();
+            if let Err(err) = data
+                .world
+                .read_resource::<MatchStats>()
+                .export(&game_match, STATS_DIR)
+            {
+                error!("failed to save the match statistics: {}", err);
+            }
+        }
+        Trans::Push(Box::new(ResultsState {
+            outcome,
+            labels: Vec::new(),
+        }))
     }
 
     fn on_resume(&mut self, data: StateData<'_, GameData<'_, '_>>) {
-        let game_time_controller = &mut *data.world.write_resource::<GameTimeController>();
-        info!("on_resume {:?}", game_time_controller.stopwatch.elapsed());
-        game_time_controller.stopwatch.start();
-        *data.world.write_resource::<CurrentState>() = CurrentState::Running;
+        let round_over = *data.world.read_resource::<CurrentState>() == CurrentState::Results;
+        if round_over {
+            if data.world.read_resource::<Match>().winner().is_some() {
+                data.world.insert(Match::new(self.settings.rounds_to_win));
+                data.world.insert(MatchStats::default());
+            }
+            self.next_round(data.world);
+        } else if data.world.remove::<RestartRound>().is_some() {
+            self.restart_round(data.world);
+        }
+        if !self.map_failed {
+            *data.world.write_resource::<CurrentState>() = CurrentState::Running;
+        }
     }
 
     fn handle_event(
         &mut self,
-        _data: StateData<'_, GameData<'_, '_>>,
+        data: StateData<'_, GameData<'_, '_>>,
         event: StateEvent,
     ) -> SimpleTrans {
         if let StateEvent::Window(event) = &event {
             // Check if the window should be closed
-            if is_close_requested(&event) || is_key_down(&event, VirtualKeyCode::Escape) {
+            if is_close_requested(&event) {
                 return Trans::Quit;
             }
 
-            if is_key_down(&event, VirtualKeyCode::P) {
-                // Pause the game by going to the `PausedState`.
-                return Trans::Push(Box::new(PausedState));
+            if let Some(mut player) = data.world.try_fetch_mut::<ReplayPlayer>() {
+                if is_key_down(&event, VirtualKeyCode::Space) {
+                    player.paused = !player.paused;
+                } else if is_key_down(&event, VirtualKeyCode::F) {
+                    player.fast_forward = !player.fast_forward;
+                } else if is_key_down(&event, VirtualKeyCode::Period) {
+                    player.step();
+                }
+            }
+
+            if is_key_down(&event, VirtualKeyCode::P) || is_key_down(&event, VirtualKeyCode::Escape)
+            {
+                // Pause the game by going to the `PausedState`, which also
+                // leads back to the menu
+                return Trans::Push(Box::new(PausedState::default()));
             }
 
             // Listen to any key events
@@ -249,20 +1189,201 @@ impl SimpleState for GameplayState {
     }
 }
 
-fn init_camera(world: &mut World, _dimensions: &ScreenDimensions) {
-    // Center the camera in the middle of the screen, and let it cover
-    // the entire screen
+impl GameplayState {
+    pub fn new(settings: MatchSettings) -> Self {
+        GameplayState {
+            settings,
+            map: None,
+            round_setup: None,
+            map_failed: false,
+        }
+    }
+
+    /// Plays on `map` as it is, such as a map being edited.
+    pub fn with_map(settings: MatchSettings, map: Map) -> Self {
+        GameplayState {
+            settings,
+            map: Some(map),
+            round_setup: None,
+            map_failed: false,
+        }
+    }
+
+    /// Starts the round after the current one, or the one the host is playing
+    /// when we are a client.
+    fn next_round(&mut self, world: &mut World) {
+        let seed = world.write_resource::<GameRng>().0.gen();
+        let hosted = world.try_fetch::<NetClient>().and_then(|client| {
+            client.snapshot().map(|snapshot| {
+                // the teams are taken in turns, the highest one tells how many
+                let teams = snapshot
+                    .players
+                    .iter()
+                    .filter_map(|player| player.team)
+                    .max()
+                    .map_or(0, |team| team + 1);
+                let setup = RoundSetup {
+                    map: snapshot.map.clone(),
+                    seed,
+                    tick_limit: snapshot.tick_limit,
+                    teams,
+                    rules: snapshot.rules.clone(),
+                };
+                (setup, snapshot.round)
+            })
+        });
+        let replayed = world
+            .try_fetch_mut::<ReplayPlayer>()
+            .and_then(|mut player| player.next_round());
+        let round = world
+            .try_fetch::<Simulation>()
+            .map_or(0, |simulation| simulation.round + 1);
+        let teams = self.settings.teams;
+        let rules = (*world.read_resource::<GameRules>()).clone();
+        let (setup, round) = match (hosted, replayed) {
+            (Some(hosted), _) => hosted,
+            (None, Some((map, tick_limit))) => {
+                let setup = RoundSetup {
+                    map,
+                    seed,
+                    tick_limit,
+                    teams,
+                    rules,
+                };
+                (setup, round)
+            }
+            (None, None) => {
+                let mut map = match (&self.map, &self.settings.generator) {
+                    (Some(map), _) => map.clone(),
+                    (None, Some(generator)) => generator::generate(generator, seed),
+                    (None, None) => match read_map(&self.settings.map_path) {
+                        Ok(map) => map,
+                        Err(err) => {
+                            // the file changed since the menu loaded it
+                            error!("failed to load the map {}: {}", self.settings.map_path, err);
+                            self.map_failed = true;
+                            *world.write_resource::<CurrentState>() = CurrentState::Paused;
+                            return;
+                        }
+                    },
+                };
+                let players = (self.settings.humans + self.settings.bots).max(1);
+                map.spawn_points.truncate(players as usize);
+                let setup = RoundSetup {
+                    map,
+                    seed,
+                    tick_limit: rules.round_ticks(),
+                    teams,
+                    rules,
+                };
+                (setup, round)
+            }
+        };
+        if let Some(mut recorder) = world.try_fetch_mut::<ReplayRecorder>() {
+            recorder.start_round(setup.map.clone(), setup.tick_limit);
+        }
+        let friendly_fire = self.settings.friendly_fire;
+        start_round(world, round, setup.clone(), friendly_fire);
+        self.round_setup = Some(setup);
+    }
+
+    /// Plays the round again from the start, on the same map and with the same
+    /// seed. It gets a new number all the same, for clients to follow.
+    fn restart_round(&self, world: &mut World) {
+        let setup = match &self.round_setup {
+            Some(setup) => setup.clone(),
+            None => return,
+        };
+        let round = world.read_resource::<Simulation>().round + 1;
+        if let Some(mut recorder) = world.try_fetch_mut::<ReplayRecorder>() {
+            recorder.restart_round();
+        }
+        info!("restarting the round");
+        let friendly_fire = self.settings.friendly_fire;
+        start_round(world, round, setup, friendly_fire);
+    }
+}
+
+fn save_replay(world: &World) {
+    if let Some(recorder) = world.try_fetch::<ReplayRecorder>() {
+        if let Err(err) = recorder.save() {
+            error!("failed to save the replay: {}", err);
+        }
+    }
+}
+
+/// The round the host is playing, when we are a client.
+fn host_round(world: &World) -> Option<u32> {
+    world
+        .try_fetch::<NetClient>()
+        .and_then(|client| client.snapshot().map(|snapshot| snapshot.round))
+}
+
+/// Wipes whatever is left of the previous round and sets up round number
+/// `round` from `setup`.
+fn start_round(world: &mut World, round: u32, setup: RoundSetup, friendly_fire: bool) {
+    world.delete_all();
+    let map = setup.map;
+
+    info!(
+        "loaded map {:?} by {:?}, {}x{} tiles, {} power-ups",
+        map.name,
+        map.author,
+        map.width(),
+        map.height(),
+        map.power_ups.len()
+    );
+
+    if world.read_resource::<AudioSettings>().enabled {
+        audio::play_music(world, &map.music);
+    }
+
+    // Get the screen dimensions so we can initialize the camera and
+    // place our sprites correctly later. We'll clone this since we'll
+    // pass the world mutably to the following functions.
+    let dimensions = (*world.read_resource::<ScreenDimensions>()).clone();
+
+    // Place the camera
+    init_camera(world, &map, &dimensions);
+
+    // Display our sprites
+    let sprite_sheet_list = (*world.read_resource::<SpriteSheetList>()).clone();
+    let sprites: Vec<SpriteRender> = (0..5)
+        .map(|i| SpriteRender {
+            sprite_sheet: sprite_sheet_list.get(AssetType::Bomb).unwrap().clone(),
+            sprite_number: i,
+        })
+        .collect();
+    init_sprites_map(world, &map, &sprites, &dimensions);
+    let mut simulation = Simulation::new(map, setup.seed, setup.rules);
+    simulation.round = round;
+    simulation.tick_limit = setup.tick_limit;
+    simulation.assign_teams(setup.teams);
+    simulation.friendly_fire = friendly_fire;
+    world.insert(simulation);
+    world.insert(GameEvents::default());
+    player::init_players(world, &sprites);
+    hud::init_hud(world, &sprites[2]);
+    power_up::init_power_ups(world, &sprite_sheet_list);
+    world.insert(GameTimeController::default());
+}
+
+pub fn init_camera(world: &mut World, map: &Map, _dimensions: &ScreenDimensions) {
+    // Cover the arena and the HUD strip above it, so the HUD never hides a
+    // tile
+    let width = map.arena_width();
+    let height = map.arena_height() + HUD_HEIGHT;
     let mut transform = Transform::default();
-    transform.set_translation_xyz(ARENA_WIDTH * 0.5, ARENA_HEIGHT * 0.5, 1.);
+    transform.set_translation_xyz(width * 0.5, height * 0.5, 1.);
 
     world
         .create_entity()
-        .with(Camera::standard_2d(ARENA_WIDTH, ARENA_HEIGHT))
+        .with(Camera::standard_2d(width, height))
         .with(transform)
         .build();
 }
 
-fn load_sprites(world: &mut World) -> SpriteSheetList {
+pub fn load_sprites(world: &mut World) -> SpriteSheetList {
     // Load the texture for our sprites. We'll later need to
     // add a handle to this texture to our `SpriteRender`s, so
     // we need to keep a reference to it.
@@ -299,9 +1420,20 @@ fn load_sprites(world: &mut World) -> SpriteSheetList {
             &sheet_storage,
         )
     };
+    let sheet_handle_power_up = {
+        let loader = world.read_resource::<Loader>();
+        let sheet_storage = world.read_resource::<AssetStorage<SpriteSheet>>();
+        loader.load(
+            "sprites/power_ups.ron",
+            SpriteSheetFormat(texture_handle.clone()),
+            (),
+            &sheet_storage,
+        )
+    };
     let mut sprite_sheet_list = SpriteSheetList::default();
     sprite_sheet_list.insert(AssetType::Bomb, sheet_handle);
     sprite_sheet_list.insert(AssetType::Explosion, sheet_handle_explosion);
+    sprite_sheet_list.insert(AssetType::PowerUp, sheet_handle_power_up);
     sprite_sheet_list
 
     // Create our sprite renders. Each will have a handle to the texture
@@ -315,30 +1447,31 @@ fn load_sprites(world: &mut World) -> SpriteSheetList {
     //     .collect()
 }
 
-fn init_sprites_map(
+pub fn init_sprites_map(
     world: &mut World,
-    map: &MapTiles,
+    map: &Map,
     sprites: &[SpriteRender],
     _dimensions: &ScreenDimensions,
 ) {
-    for (i, row) in map.iter().enumerate() {
-        for (j, col) in row.iter().enumerate() {
-            let x = (i as f32) * (ARENA_WIDTH / TILE_COUNT_HORIZONTAL) + TILE_WIDTH_HALF;
-            let y = (j as f32) * (ARENA_HEIGHT / TILE_COUNT_VERTICAL) + TILE_HEIGHT_HALF;
-            let mut transform = Transform::default();
-            transform.set_translation_xyz(x, y, 0.);
-
-            let sprite = match col.status {
-                TileStatus::Wall => sprites[4].clone(),
-                TileStatus::Free => sprites[1].clone(),
-                TileStatus::PermanentWall => sprites[0].clone(),
-            };
-
-            // Create an entity for each sprite and attach the `SpriteRender` as
-            // well as the transform. If you want to add behaviour to your sprites,
-            // you'll want to add a custom `Component` that will identify them, and a
-            // `System` that will iterate over them. See https://book.amethyst.rs/stable/concepts/system.html
-            world.create_entity().with(sprite).with(transform).build();
-        }
+    for tile in map.tiles() {
+        let [i, j] = tile.coordinates;
+        let x = (i as f32) * TILE_WIDTH + TILE_WIDTH_HALF;
+        let y = (j as f32) * TILE_HEIGHT + TILE_HEIGHT_HALF;
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(x, y, 0.);
+
+        let sprite = sprites[tile::sprite_number(tile.status)].clone();
+
+        // Create an entity for each sprite and attach the `SpriteRender` as
+        // well as the transform. The `Tile` lets the `TileSystem` find the
+        // sprite again when the tile changes.
+        world
+            .create_entity()
+            .with(sprite)
+            .with(transform)
+            .with(Tile {
+                coordinates: tile.coordinates,
+            })
+            .build();
     }
 }
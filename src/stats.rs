@@ -0,0 +1,200 @@
+use log::info;
+
+use serde::Serialize;
+
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::sim::stats::{DeathCause, PlayerStats};
+use crate::sim::{Match, Side, Simulation, Stats, TICKS_PER_SECOND};
+
+/// Where the statistics of every match are written once it is over.
+pub const STATS_DIR: &str = "stats";
+
+/// What every player did over the rounds of the match played so far.
+#[derive(Default)]
+pub struct MatchStats {
+    pub rounds: u32,
+    /// Number of the first round of the match, see `Simulation::round`.
+    first_round: u32,
+    pub stats: Stats,
+    /// Side every player played on, as of the last round.
+    pub sides: BTreeMap<u8, Side>,
+}
+
+impl MatchStats {
+    /// Adds up the stats of the round `simulation` just played.
+    pub fn record(&mut self, simulation: &Simulation) {
+        if self.rounds == 0 {
+            self.first_round = simulation.round;
+        }
+        self.rounds += 1;
+        self.stats.add(&simulation.stats);
+        for player in &simulation.players {
+            self.sides.insert(player.number, player.side());
+        }
+    }
+
+    /// Writes the stats as `match_<time>.json`, along with
+    /// `match_<time>.csv` and `match_<time>_deaths.csv`, into `dir`. Later
+    /// matches of the same second get `_2`, `_3` and so on after the time.
+    /// Returns the path of the JSON file.
+    pub fn export<P: AsRef<Path>>(&self, game_match: &Match, dir: P) -> io::Result<PathBuf> {
+        let dir = dir.as_ref();
+        fs::create_dir_all(dir)?;
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        // matches ending within the same second, say run from a script, get
+        // numbered rather than overwriting each other; the JSON file claims
+        // the name
+        let mut number = 1;
+        let (name, json_path, file) = loop {
+            let name = if number == 1 {
+                format!("match_{}", time)
+            } else {
+                format!("match_{}_{}", time, number)
+            };
+            let json_path = dir.join(format!("{}.json", name));
+            match OpenOptions::new()
+                .write(true)
+                .create_new(true)
+                .open(&json_path)
+            {
+                Ok(file) => break (name, json_path, file),
+                Err(ref err) if err.kind() == io::ErrorKind::AlreadyExists => number += 1,
+                Err(err) => return Err(err),
+            }
+        };
+        let report = self.report(game_match);
+
+        let writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(writer, &report)?;
+
+        let mut writer = BufWriter::new(File::create(dir.join(format!("{}.csv", name)))?);
+        writeln!(
+            writer,
+            "player,team,wins,kills,suicides,deaths,bombs_placed,walls_destroyed,\
+             power_ups_collected,seconds_alive"
+        )?;
+        for player in &report.players {
+            let stats = player.stats;
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{},{},{:.2}",
+                player.player,
+                player.team.map_or(String::new(), |team| team.to_string()),
+                player.wins,
+                stats.kills,
+                stats.suicides,
+                stats.deaths,
+                stats.bombs_placed,
+                stats.walls_destroyed,
+                stats.power_ups_collected,
+                player.seconds_alive
+            )?;
+        }
+
+        let path = dir.join(format!("{}_deaths.csv", name));
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "round,player,cause,killer,second")?;
+        for death in &report.deaths {
+            writeln!(
+                writer,
+                "{},{},{},{},{:.2}",
+                death.round,
+                death.player,
+                death.cause,
+                death
+                    .killer
+                    .map_or(String::new(), |killer| killer.to_string()),
+                death.second
+            )?;
+        }
+        info!("saved the match statistics to {}", json_path.display());
+        Ok(json_path)
+    }
+
+    /// The stats as they are exported, numbering players and teams from 1
+    /// like the game does.
+    fn report<'a>(&'a self, game_match: &Match) -> Report<'a> {
+        let players = self
+            .stats
+            .players
+            .iter()
+            .map(|(number, stats)| {
+                let side = self.sides.get(number).cloned();
+                PlayerReport {
+                    player: number + 1,
+                    team: match side {
+                        Some(Side::Team(team)) => Some(team + 1),
+                        _ => None,
+                    },
+                    wins: side.map_or(0, |side| game_match.wins(side)),
+                    stats,
+                    seconds_alive: seconds(stats.ticks_alive),
+                }
+            })
+            .collect();
+        let deaths = self
+            .stats
+            .deaths
+            .iter()
+            .map(|death| DeathReport {
+                round: death.round - self.first_round + 1,
+                player: death.player_number + 1,
+                cause: match death.cause {
+                    DeathCause::Bomb(owner) if owner == death.player_number => "suicide",
+                    DeathCause::Bomb(_) => "bomb",
+                    DeathCause::Crushed => "crushed",
+                },
+                killer: match death.cause {
+                    DeathCause::Bomb(owner) => Some(owner + 1),
+                    DeathCause::Crushed => None,
+                },
+                second: seconds(death.tick),
+            })
+            .collect();
+        Report {
+            rounds: self.rounds,
+            winner: game_match.winner().map(|side| side.to_string()),
+            players,
+            deaths,
+        }
+    }
+}
+
+fn seconds(ticks: u32) -> f32 {
+    ticks as f32 / TICKS_PER_SECOND as f32
+}
+
+#[derive(Serialize)]
+struct Report<'a> {
+    rounds: u32,
+    winner: Option<String>,
+    players: Vec<PlayerReport<'a>>,
+    deaths: Vec<DeathReport>,
+}
+
+#[derive(Serialize)]
+struct PlayerReport<'a> {
+    player: u8,
+    team: Option<u8>,
+    wins: u8,
+    #[serde(flatten)]
+    stats: &'a PlayerStats,
+    seconds_alive: f32,
+}
+
+#[derive(Serialize)]
+struct DeathReport {
+    round: u32,
+    player: u8,
+    cause: &'static str,
+    killer: Option<u8>,
+    /// Time into the round.
+    second: f32,
+}
@@ -1,16 +1,14 @@
 use amethyst::core::{SystemDesc, Transform};
 use amethyst::derive::SystemDesc;
-use amethyst::ecs::{
-    Entities, Join, LazyUpdate, Read, ReadStorage, System, SystemData, World, WriteStorage,
-};
-use amethyst::input::{InputHandler, StringBindings};
+use amethyst::ecs::{Entities, Join, Read, ReadExpect, System, SystemData, World, WriteStorage};
+use amethyst::renderer::SpriteRender;
 
-use log::info;
+use crate::sim::map::{TILE_HEIGHT, TILE_HEIGHT_HALF, TILE_WIDTH, TILE_WIDTH_HALF};
+use crate::sim::GameEvent;
+use crate::state::{GameEvents, SpriteSheetList};
 
-use crate::state::{GameTimeController, Map, SpriteSheetList};
-
-use crate::entities::bomb::spawn_bomb;
-use crate::entities::player::Player;
+use crate::entities::animation::{Animation, Animations};
+use crate::entities::bomb::{spawn_bomb, Bomb};
 
 #[derive(SystemDesc)]
 pub struct ActionsSystem;
@@ -18,45 +16,60 @@ pub struct ActionsSystem;
 impl<'s> System<'s> for ActionsSystem {
     type SystemData = (
         Entities<'s>,
-        Read<'s, LazyUpdate>,
-        ReadStorage<'s, Transform>,
-        WriteStorage<'s, Player>,
+        WriteStorage<'s, Bomb>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Animation>,
+        WriteStorage<'s, Transform>,
         Read<'s, SpriteSheetList>,
-        Read<'s, Map>,
-        Read<'s, InputHandler<StringBindings>>,
-        Read<'s, GameTimeController>,
+        ReadExpect<'s, Animations>,
+        Read<'s, GameEvents>,
     );
 
     fn run(
         &mut self,
         (
             entities,
-            lazy_update,
-            transforms,
-            mut players,
+            mut bombs,
+            mut sprite_renders,
+            mut animation_storage,
+            mut transforms,
             sprite_sheet_list,
-            map,
-            input,
-            game_time_controller,
+            animations,
+            game_events,
         ): Self::SystemData,
     ) {
-        let fire_input = input.action_is_down("fire").unwrap();
-        if fire_input {
-            for (player, transform) in (&mut players, &transforms).join() {
-                if player.number != 0 || player.num_bombs == 0 {
-                    return;
+        for event in &game_events.events {
+            match event {
+                GameEvent::BombPlaced {
+                    id, coordinates, ..
+                } => {
+                    spawn_bomb(
+                        &entities,
+                        (
+                            &mut bombs,
+                            &mut sprite_renders,
+                            &mut animation_storage,
+                            &mut transforms,
+                        ),
+                        &sprite_sheet_list,
+                        &animations,
+                        *id,
+                        *coordinates,
+                    );
+                }
+                GameEvent::BombMoved { id, coordinates } => {
+                    for (bomb, transform) in (&bombs, &mut transforms).join() {
+                        if bomb.id == *id {
+                            transform.set_translation_x(
+                                coordinates[0] as f32 * TILE_WIDTH + TILE_WIDTH_HALF,
+                            );
+                            transform.set_translation_y(
+                                coordinates[1] as f32 * TILE_HEIGHT + TILE_HEIGHT_HALF,
+                            );
+                        }
+                    }
                 }
-                player.num_bombs -= 1;
-                info!("spawning, {}", player.num_bombs);
-                spawn_bomb(
-                    &entities,
-                    &transform,
-                    &lazy_update,
-                    &sprite_sheet_list,
-                    &map,
-                    &game_time_controller.stopwatch,
-                    player.number,
-                );
+                _ => {}
             }
         }
     }
@@ -0,0 +1,44 @@
+use amethyst::core::timing::Time;
+use amethyst::core::SystemDesc;
+use amethyst::derive::SystemDesc;
+use amethyst::ecs::{Entities, Join, Read, System, SystemData, World, WriteStorage};
+use amethyst::renderer::SpriteRender;
+
+use crate::entities::animation::Animation;
+use crate::replay::ReplayPlayer;
+
+/// Moves every animation forward by the game time elapsed this frame. Being
+/// pausable, it stands still with the rest of the game in `PausedState`.
+#[derive(SystemDesc)]
+pub struct AnimationSystem;
+
+impl<'s> System<'s> for AnimationSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, Animation>,
+        WriteStorage<'s, SpriteRender>,
+        Read<'s, Time>,
+        Option<Read<'s, ReplayPlayer>>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, mut animations, mut sprite_renders, time, replay_player): Self::SystemData,
+    ) {
+        // replays go at the pace of the viewer
+        let speed = replay_player.map_or(1, |player| player.speed());
+        let seconds = time.delta_seconds() * speed as f32;
+        for (entity, animation, sprite_render) in
+            (&*entities, &mut animations, &mut sprite_renders).join()
+        {
+            animation.advance(seconds);
+            if animation.remove_when_done && animation.is_done() {
+                entities.delete(entity).unwrap();
+                continue;
+            }
+            if let Some(sprite_number) = animation.sprite_number() {
+                sprite_render.sprite_number = sprite_number;
+            }
+        }
+    }
+}
@@ -0,0 +1,60 @@
+use amethyst::assets::AssetStorage;
+use amethyst::audio::{output::Output, Source};
+use amethyst::core::SystemDesc;
+use amethyst::derive::SystemDesc;
+use amethyst::ecs::{Read, ReadExpect, System, SystemData, World, Write};
+
+use crate::audio::{play_sound, AudioSettings, Music, Sounds};
+use crate::sim::{GameEvent, Simulation};
+use crate::state::GameEvents;
+
+/// Plays the sound of every `GameEvents` that has one and hurries the music
+/// up once the sudden death has started.
+#[derive(SystemDesc)]
+pub struct AudioSystem;
+
+impl<'s> System<'s> for AudioSystem {
+    type SystemData = (
+        Read<'s, GameEvents>,
+        ReadExpect<'s, Simulation>,
+        Option<Read<'s, Sounds>>,
+        Read<'s, AssetStorage<Source>>,
+        Option<Read<'s, Output>>,
+        Read<'s, AudioSettings>,
+        Write<'s, Music>,
+    );
+
+    fn run(
+        &mut self,
+        (game_events, simulation, sounds, storage, output, settings, mut music): Self::SystemData,
+    ) {
+        music.hurry_up = simulation.sudden_death_started();
+        let sounds = match sounds {
+            Some(sounds) => sounds,
+            None => return,
+        };
+        let output = output.as_ref().map(|output| &**output);
+        let volume = settings.effects_volume;
+        for event in &game_events.events {
+            match event {
+                GameEvent::BombPlaced { .. } => {
+                    play_sound(&sounds.bomb_placed, &storage, output, volume);
+                    play_sound(&sounds.fuse, &storage, output, volume);
+                }
+                GameEvent::ExplosionCreated { .. } => {
+                    play_sound(&sounds.explosion, &storage, output, volume)
+                }
+                GameEvent::WallDestroyed { .. } => {
+                    play_sound(&sounds.wall_destroyed, &storage, output, volume)
+                }
+                GameEvent::PowerUpCollected { .. } => {
+                    play_sound(&sounds.power_up, &storage, output, volume)
+                }
+                GameEvent::PlayerDied { .. } => {
+                    play_sound(&sounds.player_died, &storage, output, volume)
+                }
+                _ => {}
+            }
+        }
+    }
+}
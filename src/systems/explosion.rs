@@ -1,29 +1,17 @@
 use amethyst::core::{SystemDesc, Transform};
 use amethyst::derive::SystemDesc;
 use amethyst::ecs::{
-    Entities, Join, LazyUpdate, Read, ReadStorage, System, SystemData, World, Write, WriteStorage,
+    Entities, Join, LazyUpdate, Read, ReadExpect, ReadStorage, System, SystemData, World,
 };
-use amethyst::renderer::SpriteRender;
+use amethyst::renderer::{resources::Tint, SpriteRender};
 
-use ncollide2d::bounding_volume::{BoundingVolume, AABB};
-use ncollide2d::math::Point;
-
-use log::info;
-
-use std::time::Duration;
-
-use crate::state::{
-    AssetType, GameTimeController, Map, SpriteSheetList, TileStatus, ARENA_HEIGHT, ARENA_WIDTH,
-    TILE_COUNT_HORIZONTAL, TILE_COUNT_VERTICAL, TILE_HEIGHT, TILE_HEIGHT_HALF, TILE_WIDTH,
-    TILE_WIDTH_HALF,
-};
+use crate::sim::{GameEvent, GameRules, Simulation};
+use crate::state::{GameEvents, SpriteSheetList};
 
+use crate::entities::animation::{Animation, Animations};
 use crate::entities::bomb::Bomb;
 use crate::entities::explosion::{create_explosion, Explosion};
-use crate::entities::player::{Player, PLAYER_HEIGHT_HALF, PLAYER_WIDTH_HALF};
-
-const THREE_SECS: Duration = Duration::from_secs(3);
-const EXPLOSION_DURATION: Duration = Duration::from_millis(500);
+use crate::entities::player::Player;
 
 #[derive(SystemDesc)]
 pub struct ExplosionSystem;
@@ -32,13 +20,16 @@ impl<'s> System<'s> for ExplosionSystem {
     type SystemData = (
         Entities<'s>,
         Read<'s, LazyUpdate>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Bomb>,
+        ReadStorage<'s, Explosion>,
         ReadStorage<'s, Transform>,
-        WriteStorage<'s, Player>,
+        ReadStorage<'s, SpriteRender>,
+        ReadStorage<'s, Tint>,
         Read<'s, SpriteSheetList>,
-        Write<'s, Map>,
-        WriteStorage<'s, Bomb>,
-        WriteStorage<'s, Explosion>,
-        Read<'s, GameTimeController>,
+        ReadExpect<'s, Animations>,
+        Read<'s, GameEvents>,
+        Option<ReadExpect<'s, Simulation>>,
     );
 
     fn run(
@@ -46,149 +37,75 @@ impl<'s> System<'s> for ExplosionSystem {
         (
             entities,
             lazy_update,
+            players,
+            bombs,
+            explosions,
             transforms,
-            mut players,
+            sprite_renders,
+            tints,
             sprite_sheet_list,
-            mut map,
-            mut bombs,
-            mut explosions,
-            game_time_controller,
+            animations,
+            game_events,
+            simulation,
         ): Self::SystemData,
     ) {
-        for (entity, explosion) in (&*entities, &mut explosions).join() {
-            for (entity, player, transform) in (&*entities, &mut players, &transforms).join() {
-                let bbox = AABB::new(
-                    Point::new(
-                        transform.translation().x - PLAYER_WIDTH_HALF,
-                        transform.translation().y - PLAYER_HEIGHT_HALF,
-                    ),
-                    Point::new(
-                        transform.translation().x + PLAYER_WIDTH_HALF,
-                        transform.translation().y + PLAYER_HEIGHT_HALF,
-                    ),
-                );
-                let collided = explosion.collision_polygon.intersects(&bbox);
-                if collided {
-                    entities.delete(entity).unwrap();
-                    info!("player {} dead", player.number);
-                }
-            }
-            let duration = game_time_controller
-                .stopwatch
-                .elapsed()
-                .checked_sub(explosion.created_time);
-            if let Some(d) = duration {
-                if d >= EXPLOSION_DURATION {
-                    entities.delete(entity).unwrap();
-                }
-            }
-        }
-        for (entity, bomb, bomb_transform) in (&*entities, &mut bombs, &transforms).join() {
-            let duration = game_time_controller
-                .stopwatch
-                .elapsed()
-                .checked_sub(bomb.created_time);
-            if let Some(d) = duration {
-                if d < THREE_SECS {
-                    continue;
-                }
-                entities.delete(entity).unwrap();
-                let bomb_tile = map.get_tile(
-                    bomb_transform.translation().x,
-                    bomb_transform.translation().y,
-                );
-                let coordinates = bomb_tile.coordinates;
-                let x = coordinates[0] as i32;
-                let y = coordinates[1] as i32;
-                let initial_coordinates = (
-                    Point::new(
-                        x as f32 * ARENA_WIDTH / TILE_COUNT_HORIZONTAL,
-                        y as f32 * ARENA_HEIGHT / TILE_COUNT_VERTICAL,
-                    ),
-                    Point::new(
-                        x as f32 * ARENA_WIDTH / TILE_COUNT_HORIZONTAL + TILE_WIDTH,
-                        y as f32 * ARENA_HEIGHT / TILE_COUNT_VERTICAL + TILE_HEIGHT,
-                    ),
-                );
-                let mut collision_polygons: Vec<AABB<f32>> = Vec::with_capacity(4);
-                for i in 0..4 {
-                    // check all four directions
-                    let mut collision_polygon;
-                    for j in 1..(bomb.power + 1) {
-                        let j = j as i32;
-                        let (x, y) = match i {
-                            0 => (x, y + j),
-                            1 => (x + j, y),
-                            2 => (x, y - j),
-                            3 => (x - j, y),
-                            _ => panic!("HOW?"),
-                        };
-                        if x < 0 || x > 12 || y < 0 || y > 10 {
-                            continue;
+        let flame_time = simulation
+            .as_ref()
+            .map_or(GameRules::default().flame_time, |simulation| {
+                simulation.rules.flame_time
+            });
+        for event in &game_events.events {
+            match event {
+                GameEvent::BombExploded { id } | GameEvent::BombCrushed { id } => {
+                    for (entity, bomb) in (&*entities, &bombs).join() {
+                        if bomb.id == *id {
+                            entities.delete(entity).unwrap();
                         }
-                        let next_tile = map.get_tile_by_key(x as usize, y as usize);
-                        match next_tile.status {
-                            TileStatus::Wall => {
-                                map.update_tile(x as usize, y as usize, TileStatus::Free);
-                                let new_tile_entity = entities.create();
-                                let mut new_tile_transform = Transform::default();
-                                new_tile_transform.set_translation_xyz(
-                                    x as f32 * ARENA_WIDTH / TILE_COUNT_HORIZONTAL
-                                        + TILE_WIDTH_HALF,
-                                    y as f32 * ARENA_HEIGHT / TILE_COUNT_VERTICAL
-                                        + TILE_HEIGHT_HALF,
-                                    0.1,
-                                );
-                                let sprite_render = SpriteRender {
-                                    sprite_sheet: sprite_sheet_list
-                                        .get(AssetType::Bomb)
-                                        .unwrap()
-                                        .clone(),
-                                    sprite_number: 1,
-                                };
-                                lazy_update.insert(new_tile_entity, sprite_render);
-                                lazy_update.insert(new_tile_entity, new_tile_transform);
-                                break;
-                            }
-                            TileStatus::Free => {
-                                if i < 2 {
-                                    collision_polygon = AABB::new(
-                                        initial_coordinates.0,
-                                        Point::new(
-                                            x as f32 * ARENA_WIDTH / TILE_COUNT_HORIZONTAL
-                                                + TILE_WIDTH,
-                                            y as f32 * ARENA_HEIGHT / TILE_COUNT_VERTICAL
-                                                + TILE_HEIGHT,
-                                        ),
-                                    );
-                                } else {
-                                    collision_polygon = AABB::new(
-                                        Point::new(
-                                            x as f32 * ARENA_WIDTH / TILE_COUNT_HORIZONTAL,
-                                            y as f32 * ARENA_HEIGHT / TILE_COUNT_VERTICAL,
-                                        ),
-                                        initial_coordinates.1,
-                                    );
-                                }
-                            }
-                            TileStatus::PermanentWall => break,
-                        }
-                        collision_polygons.push(collision_polygon);
                     }
+                }
+                GameEvent::ExplosionCreated { id, flames } => {
                     create_explosion(
                         &entities,
                         &lazy_update,
                         &sprite_sheet_list,
-                        &collision_polygons,
-                        &AABB::new(initial_coordinates.0, initial_coordinates.1),
-                        &game_time_controller.stopwatch,
+                        &animations,
+                        *id,
+                        flames,
+                        flame_time,
                     );
-                    for player in (&mut players).join() {
-                        if player.number == bomb.player_number {
-                            player.num_bombs = 1;
+                }
+                GameEvent::ExplosionFaded { id } => {
+                    for (entity, explosion) in (&*entities, &explosions).join() {
+                        if explosion.id == *id {
+                            entities.delete(entity).unwrap();
+                        }
+                    }
+                }
+                GameEvent::PlayerDied { number, .. } => {
+                    for (entity, player, transform, sprite_render, tint) in (
+                        &*entities,
+                        &players,
+                        &transforms,
+                        &sprite_renders,
+                        tints.maybe(),
+                    )
+                        .join()
+                    {
+                        if player.number == *number {
+                            // the body stays behind for its death animation
+                            let body = entities.create();
+                            lazy_update.insert(body, transform.clone());
+                            lazy_update.insert(body, sprite_render.clone());
+                            if let Some(tint) = tint {
+                                lazy_update.insert(body, *tint);
+                            }
+                            lazy_update
+                                .insert(body, Animation::once_then_remove(&animations.death));
+                            entities.delete(entity).unwrap();
                         }
                     }
                 }
+                _ => {}
             }
         }
     }
@@ -0,0 +1,95 @@
+use amethyst::core::SystemDesc;
+use amethyst::derive::SystemDesc;
+use amethyst::ecs::{Join, ReadExpect, ReadStorage, System, SystemData, World, WriteStorage};
+use amethyst::renderer::SpriteRender;
+use amethyst::ui::UiText;
+
+use crate::entities::animation::Animations;
+use crate::entities::hud::{HudLabel, HudPortrait};
+use crate::sim::{Match, Simulation, TICKS_PER_SECOND};
+
+const WHITE: [f32; 4] = [1., 1., 1., 1.];
+const GREY: [f32; 4] = [0.5, 0.5, 0.5, 1.];
+/// The clock turns red once the walls start falling.
+const RED: [f32; 4] = [1., 0.3, 0.3, 1.];
+
+/// Keeps the HUD up to date with the `Simulation`. It is not paused with the
+/// game, so the wins shown stay right during the results.
+#[derive(SystemDesc)]
+pub struct HudSystem;
+
+impl<'s> System<'s> for HudSystem {
+    type SystemData = (
+        ReadStorage<'s, HudLabel>,
+        ReadStorage<'s, HudPortrait>,
+        WriteStorage<'s, UiText>,
+        WriteStorage<'s, SpriteRender>,
+        Option<ReadExpect<'s, Simulation>>,
+        Option<ReadExpect<'s, Match>>,
+        ReadExpect<'s, Animations>,
+    );
+
+    fn run(
+        &mut self,
+        (labels, portraits, mut texts, mut sprite_renders, simulation, game_match, animations): Self::SystemData,
+    ) {
+        let simulation = match simulation {
+            Some(simulation) => simulation,
+            None => return,
+        };
+        for (label, text) in (&labels, &mut texts).join() {
+            let (line, color) = match label {
+                HudLabel::Clock => {
+                    let seconds =
+                        (simulation.remaining_ticks() + TICKS_PER_SECOND - 1) / TICKS_PER_SECOND;
+                    let color = if simulation.sudden_death_started() {
+                        RED
+                    } else {
+                        WHITE
+                    };
+                    (format!("{}:{:02}", seconds / 60, seconds % 60), color)
+                }
+                HudLabel::Wins(number) | HudLabel::PowerUps(number) => {
+                    let player = match simulation.player(*number) {
+                        Some(player) => player,
+                        None => continue,
+                    };
+                    let color = if player.alive { WHITE } else { GREY };
+                    let line = match label {
+                        HudLabel::Wins(_) => {
+                            let wins = game_match
+                                .as_ref()
+                                .map_or(0, |game_match| game_match.wins(player.side()));
+                            format!("P{}  Wins {}", number + 1, wins)
+                        }
+                        _ => format!(
+                            "B{}/{} F{} S{}",
+                            player.num_bombs,
+                            player.max_bombs,
+                            player.power,
+                            player.speed_level(&simulation.rules)
+                        ),
+                    };
+                    (line, color)
+                }
+            };
+            if text.text != line {
+                text.text = line;
+            }
+            text.color = color;
+        }
+
+        // the dead are shown as they fell
+        let standing = animations.walk_down.frames.first().cloned();
+        let fallen = animations.death.frames.last().cloned();
+        for (portrait, sprite_render) in (&portraits, &mut sprite_renders).join() {
+            let alive = simulation
+                .player(portrait.number)
+                .map_or(false, |player| player.alive);
+            let frame = if alive { standing } else { fallen };
+            if let Some(frame) = frame {
+                sprite_render.sprite_number = frame;
+            }
+        }
+    }
+}
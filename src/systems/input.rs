@@ -0,0 +1,34 @@
+use amethyst::core::SystemDesc;
+use amethyst::derive::SystemDesc;
+use amethyst::ecs::{Join, Read, ReadStorage, System, SystemData, World, WriteStorage};
+use amethyst::input::{InputHandler, StringBindings};
+
+use crate::entities::player::{Controls, Player};
+use crate::sim::PlayerInput;
+
+#[derive(SystemDesc)]
+pub struct PlayerInputSystem;
+
+impl<'s> System<'s> for PlayerInputSystem {
+    type SystemData = (
+        ReadStorage<'s, Player>,
+        WriteStorage<'s, PlayerInput>,
+        Read<'s, InputHandler<StringBindings>>,
+        Read<'s, Controls>,
+    );
+
+    fn run(&mut self, (players, mut inputs, input, controls): Self::SystemData) {
+        for (player, player_input) in (&players, &mut inputs).join() {
+            let bindings = match controls.binding_set(player.number) {
+                Some(bindings) => bindings,
+                None => continue,
+            };
+            *player_input = PlayerInput {
+                movement_x: input.axis_value(&bindings.leftright).unwrap_or(0.),
+                movement_y: input.axis_value(&bindings.updown).unwrap_or(0.),
+                fire: input.action_is_down(&bindings.fire).unwrap_or(false),
+                detonate: input.action_is_down(&bindings.detonate).unwrap_or(false),
+            };
+        }
+    }
+}
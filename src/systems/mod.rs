@@ -1,7 +1,23 @@
 pub use self::actions::ActionsSystem;
+pub use self::animation::AnimationSystem;
+pub use self::audio::AudioSystem;
 pub use self::explosion::ExplosionSystem;
+pub use self::hud::HudSystem;
+pub use self::input::PlayerInputSystem;
 pub use self::movement::MovementSystem;
+pub use self::net::{NetClientSystem, NetHostSystem};
+pub use self::power_up::PowerUpSystem;
+pub use self::simulation::SimulationSystem;
+pub use self::tile::TileSystem;
 
 mod actions;
+mod animation;
+mod audio;
 mod explosion;
+mod hud;
+mod input;
 mod movement;
+mod net;
+mod power_up;
+mod simulation;
+mod tile;
@@ -0,0 +1,120 @@
+use amethyst::core::SystemDesc;
+use amethyst::derive::SystemDesc;
+use amethyst::ecs::{
+    Join, Read, ReadExpect, ReadStorage, System, SystemData, World, Write, WriteExpect,
+    WriteStorage,
+};
+
+use crate::entities::player::{Controls, Player};
+use crate::net::{HostEvent, NetClient, NetHost};
+use crate::sim::{PlayerInput, Simulation};
+use crate::state::{CurrentState, GameEvents};
+
+/// Lets clients take over the players left to the AI, feeds their inputs to
+/// the `Simulation` and sends them a snapshot of every step.
+#[derive(SystemDesc)]
+pub struct NetHostSystem;
+
+impl<'s> System<'s> for NetHostSystem {
+    type SystemData = (
+        WriteStorage<'s, Player>,
+        WriteStorage<'s, PlayerInput>,
+        WriteExpect<'s, NetHost>,
+        Write<'s, Controls>,
+        Read<'s, CurrentState>,
+        Option<ReadExpect<'s, Simulation>>,
+    );
+
+    fn run(
+        &mut self,
+        (mut players, mut inputs, mut host, mut controls, current_state, simulation): Self::SystemData,
+    ) {
+        // nothing to host before the first round, from the menu
+        let simulation = match simulation {
+            Some(simulation) => simulation,
+            None => return,
+        };
+        let free_numbers: Vec<u8> = simulation
+            .players
+            .iter()
+            .map(|player| player.number)
+            .filter(|number| !controls.is_human(*number))
+            .collect();
+
+        for event in host.receive(&free_numbers) {
+            let number = match event {
+                HostEvent::Joined(number) => {
+                    controls.add_remote(number);
+                    number
+                }
+                HostEvent::Left(number) => {
+                    controls.remove_remote(number);
+                    number
+                }
+                HostEvent::Input(number, _) => number,
+            };
+            for (player, player_input) in (&mut players, &mut inputs).join() {
+                if player.number != number {
+                    continue;
+                }
+                player.is_human = controls.is_human(number);
+                match event {
+                    HostEvent::Input(_, input) => *player_input = input,
+                    _ => *player_input = PlayerInput::default(),
+                }
+            }
+        }
+
+        if *current_state == CurrentState::Running {
+            host.broadcast(simulation.snapshot());
+        }
+    }
+}
+
+/// Takes the place of the `SimulationSystem` on clients: sends the input of
+/// our player to the host and mirrors the snapshots it sends back.
+#[derive(SystemDesc)]
+pub struct NetClientSystem;
+
+impl<'s> System<'s> for NetClientSystem {
+    type SystemData = (
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, PlayerInput>,
+        WriteExpect<'s, NetClient>,
+        Write<'s, GameEvents>,
+        Read<'s, CurrentState>,
+        Option<WriteExpect<'s, Simulation>>,
+    );
+
+    fn run(
+        &mut self,
+        (players, inputs, mut client, mut game_events, current_state, simulation): Self::SystemData,
+    ) {
+        let number = client.number();
+        if let Some((_, input)) = (&players, &inputs)
+            .join()
+            .find(|(player, _)| player.number == number)
+        {
+            client.send_input(*input);
+        }
+        client.receive();
+
+        game_events.events.clear();
+        let mut simulation = match simulation {
+            Some(simulation) => simulation,
+            None => return,
+        };
+        if *current_state != CurrentState::Running {
+            return;
+        }
+        // snapshots of another round wait for the `GameplayState` to start it
+        let same_round = client
+            .snapshot()
+            .map_or(false, |snapshot| snapshot.round == simulation.round);
+        if same_round {
+            if let Some(snapshot) = client.take_update() {
+                game_events.events = simulation.apply_snapshot(snapshot);
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use amethyst::core::{SystemDesc, Transform};
+use amethyst::derive::SystemDesc;
+use amethyst::ecs::{Entities, Join, Read, System, SystemData, World, WriteStorage};
+use amethyst::renderer::SpriteRender;
+
+use crate::sim::GameEvent;
+use crate::state::{GameEvents, SpriteSheetList};
+
+use crate::entities::power_up::{spawn_power_up, PowerUp};
+
+#[derive(SystemDesc)]
+pub struct PowerUpSystem;
+
+impl<'s> System<'s> for PowerUpSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, PowerUp>,
+        WriteStorage<'s, SpriteRender>,
+        WriteStorage<'s, Transform>,
+        Read<'s, SpriteSheetList>,
+        Read<'s, GameEvents>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            entities,
+            mut power_ups,
+            mut sprite_renders,
+            mut transforms,
+            sprite_sheet_list,
+            game_events,
+        ): Self::SystemData,
+    ) {
+        for event in &game_events.events {
+            match event {
+                GameEvent::PowerUpDropped {
+                    id,
+                    kind,
+                    coordinates,
+                } => {
+                    spawn_power_up(
+                        &entities,
+                        (&mut power_ups, &mut sprite_renders, &mut transforms),
+                        &sprite_sheet_list,
+                        *id,
+                        *kind,
+                        *coordinates,
+                    );
+                }
+                GameEvent::PowerUpCollected { id, .. } | GameEvent::PowerUpDestroyed { id } => {
+                    // a power-up dropped earlier in the frame is already there
+                    for (entity, power_up) in (&*entities, &power_ups).join() {
+                        if power_up.id == *id {
+                            entities.delete(entity).unwrap();
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use amethyst::core::timing::Time;
+use amethyst::core::SystemDesc;
+use amethyst::derive::SystemDesc;
+use amethyst::ecs::{Join, Read, ReadStorage, System, SystemData, World, Write, WriteExpect};
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::entities::player::Player;
+use crate::replay::{ReplayPlayer, ReplayRecorder};
+use crate::sim::{ai, Difficulty, PlayerInput, Simulation, TICK};
+use crate::state::{GameEvents, GameTimeController};
+
+/// Beyond this, frames are too slow to catch up with and the game slows down.
+const MAX_TICKS_PER_FRAME: u32 = 5;
+
+/// Steps the `Simulation` once for every tick elapsed, with the inputs
+/// gathered this frame or the ones of the replay being played. The players
+/// that are not human are played by the AI, which thinks again before every
+/// tick as in headless matches. The other gameplay systems only mirror the
+/// resulting `GameEvents` on screen.
+#[derive(SystemDesc)]
+pub struct SimulationSystem;
+
+impl<'s> System<'s> for SimulationSystem {
+    type SystemData = (
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, PlayerInput>,
+        WriteExpect<'s, Simulation>,
+        Write<'s, GameEvents>,
+        Write<'s, GameTimeController>,
+        Read<'s, Time>,
+        Option<Write<'s, ReplayRecorder>>,
+        Option<Write<'s, ReplayPlayer>>,
+        Read<'s, Difficulty>,
+    );
+
+    fn run(
+        &mut self,
+        (
+            players,
+            player_inputs,
+            mut simulation,
+            mut game_events,
+            mut controller,
+            time,
+            mut recorder,
+            mut replay_player,
+            difficulty,
+        ): Self::SystemData,
+    ) {
+        let inputs: HashMap<u8, PlayerInput> = (&players, &player_inputs)
+            .join()
+            .map(|(player, input)| (player.number, *input))
+            .collect();
+        let bots: Vec<u8> = players
+            .join()
+            .filter(|player| !player.is_human)
+            .map(|player| player.number)
+            .collect();
+        let speed = replay_player.as_ref().map_or(1, |player| player.speed());
+        controller.accumulator += time.delta_time() * speed;
+        if replay_player
+            .as_mut()
+            .map_or(false, |player| player.take_step())
+        {
+            controller.accumulator += TICK;
+        }
+        game_events.events.clear();
+        let mut ticks = 0;
+        while controller.accumulator >= TICK {
+            if ticks == MAX_TICKS_PER_FRAME * speed.max(1) {
+                controller.accumulator = Duration::default();
+                break;
+            }
+            // nothing may happen after the end of the round
+            if simulation.outcome().is_some() {
+                break;
+            }
+            let inputs = match replay_player.as_mut() {
+                Some(player) => match player.next_inputs() {
+                    Some(inputs) => inputs,
+                    None => break,
+                },
+                None => {
+                    let mut inputs = inputs.clone();
+                    for &number in &bots {
+                        inputs.insert(number, ai::think(&simulation, number, *difficulty));
+                    }
+                    inputs
+                }
+            };
+            if let Some(recorder) = recorder.as_mut() {
+                recorder.record(&inputs);
+            }
+            controller.accumulator -= TICK;
+            let events = simulation.step(&inputs);
+            game_events.events.extend(events);
+            ticks += 1;
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use amethyst::core::{SystemDesc, Transform};
+use amethyst::derive::SystemDesc;
+use amethyst::ecs::{
+    Entities, Join, LazyUpdate, Read, ReadExpect, ReadStorage, System, SystemData, World,
+    WriteStorage,
+};
+use amethyst::renderer::SpriteRender;
+
+use crate::sim::{GameEvent, TileStatus};
+use crate::state::GameEvents;
+
+use crate::entities::animation::{Animation, Animations};
+use crate::entities::tile::{sprite_number, Tile};
+
+/// Keeps the tile sprites in line with the walls destroyed and dropped on the
+/// `Map`. Destroyed walls crumble away on top of the floor they leave.
+#[derive(SystemDesc)]
+pub struct TileSystem;
+
+impl<'s> System<'s> for TileSystem {
+    type SystemData = (
+        Entities<'s>,
+        Read<'s, LazyUpdate>,
+        ReadStorage<'s, Tile>,
+        ReadStorage<'s, Transform>,
+        WriteStorage<'s, SpriteRender>,
+        ReadExpect<'s, Animations>,
+        Read<'s, GameEvents>,
+    );
+
+    fn run(
+        &mut self,
+        (entities, lazy_update, tiles, transforms, mut sprite_renders, animations, game_events): Self::SystemData,
+    ) {
+        for event in &game_events.events {
+            let (coordinates, status) = match event {
+                GameEvent::WallDestroyed { coordinates } => (coordinates, TileStatus::Free),
+                GameEvent::WallDropped { coordinates } => (coordinates, TileStatus::PermanentWall),
+                _ => continue,
+            };
+            for (tile, transform, sprite_render) in
+                (&tiles, &transforms, &mut sprite_renders).join()
+            {
+                if tile.coordinates != *coordinates {
+                    continue;
+                }
+                if status == TileStatus::Free {
+                    let mut rubble_transform = transform.clone();
+                    rubble_transform.set_translation_z(0.1);
+                    let rubble = entities.create();
+                    lazy_update.insert(rubble, rubble_transform);
+                    lazy_update.insert(rubble, sprite_render.clone());
+                    lazy_update.insert(
+                        rubble,
+                        Animation::once_then_remove(&animations.wall_crumbling),
+                    );
+                }
+                sprite_render.sprite_number = sprite_number(status);
+            }
+        }
+    }
+}